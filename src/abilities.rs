@@ -0,0 +1,71 @@
+use crate::items::*;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AbilityEffect {
+    /// Moves the player up to `distance` tiles in one turn
+    Dash {
+        distance: usize,
+    },
+    /// Hits an adjacent enemy and knocks it back
+    ShieldBash {
+        damage: f32,
+        knockback: usize,
+    },
+    /// Attacks every adjacent enemy with the equipped weapon
+    Whirlwind,
+    Heal(f32),
+}
+impl AbilityEffect {
+    /// Whether the player has to pick a direction to use the ability in
+    pub fn is_aimed(self) -> bool {
+        matches!(
+            self,
+            AbilityEffect::Dash { .. } | AbilityEffect::ShieldBash { .. }
+        )
+    }
+}
+#[derive(PartialEq)]
+pub struct Ability {
+    pub name: &'static str,
+    /// Sprite on the items spritesheet used as the ability's icon
+    pub icon: (f32, f32),
+    /// Turns until the ability can be used again, counting the turn it's used on
+    pub cooldown: u16,
+    pub effect: AbilityEffect,
+}
+pub const DASH: Ability = Ability {
+    name: "dash",
+    icon: (2.0, 1.0),
+    cooldown: 6,
+    effect: AbilityEffect::Dash { distance: 2 },
+};
+pub const SHIELD_BASH: Ability = Ability {
+    name: "shield bash",
+    icon: (0.0, 1.0),
+    cooldown: 5,
+    effect: AbilityEffect::ShieldBash {
+        damage: 2.0,
+        knockback: 2,
+    },
+};
+pub const WHIRLWIND: Ability = Ability {
+    name: "whirlwind",
+    icon: (8.0, 0.0),
+    cooldown: 8,
+    effect: AbilityEffect::Whirlwind,
+};
+pub const MEND: Ability = Ability {
+    name: "mend",
+    icon: (9.0, 2.0),
+    cooldown: 15,
+    effect: AbilityEffect::Heal(5.0),
+};
+
+/// Abilities granted while the item is equipped
+pub static ITEM_ABILITIES: &[(Item, &Ability)] = &[
+    (Item::Armor(&IRON_ARMOR), &SHIELD_BASH),
+    (Item::Weapon(&STONE_AXE), &WHIRLWIND),
+    (Item::Weapon(&IRON_SWORD), &WHIRLWIND),
+    (Item::Weapon(&SPELLBOOK), &MEND),
+    (Item::Weapon(&SUPER_SPELLBOOK), &MEND),
+];
@@ -105,6 +105,18 @@ impl Spritesheet {
         tile_x: f32,
         tile_y: f32,
         params: Option<&DrawTextureParams>,
+    ) {
+        self.draw_tile_tinted(screen_x, screen_y, tile_x, tile_y, WHITE, params);
+    }
+    /// Same as `draw_tile`, except tinted with a color
+    pub fn draw_tile_tinted(
+        &self,
+        screen_x: f32,
+        screen_y: f32,
+        tile_x: f32,
+        tile_y: f32,
+        color: Color,
+        params: Option<&DrawTextureParams>,
     ) {
         let mut p = params.cloned().unwrap_or(DrawTextureParams::default());
         p.dest_size = p
@@ -116,6 +128,6 @@ impl Spritesheet {
             w: self.sprite_size,
             h: self.sprite_size,
         }));
-        draw_texture_ex(&self.texture, screen_x, screen_y, WHITE, p);
+        draw_texture_ex(&self.texture, screen_x, screen_y, color, p);
     }
 }
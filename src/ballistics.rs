@@ -0,0 +1,209 @@
+use macroquad::prelude::*;
+
+use crate::{Tile, utils::*};
+
+/// Furthest a projectile can travel, in tiles
+const MAX_DISTANCE: usize = TILES_HORIZONTAL;
+
+/// How a projectile behaves once fired
+#[derive(Clone, Copy, PartialEq)]
+pub struct Projectile {
+    /// Amount of actors the projectile passes through before stopping
+    pub pierce: usize,
+    /// Amount of times the projectile bounces off walls
+    pub ricochet: usize,
+    /// Radius of the area hit around where the projectile stops
+    pub splash: f32,
+}
+pub const BOLT: Projectile = Projectile {
+    pierce: 0,
+    ricochet: 0,
+    splash: 0.0,
+};
+
+/// The path a projectile takes through the dungeon
+pub struct Trajectory {
+    /// Every tile passed through, in order
+    pub path: Vec<(usize, usize)>,
+    /// Tiles of the actors hit, in order
+    pub hits: Vec<(usize, usize)>,
+}
+impl Trajectory {
+    /// Tile the projectile stopped at
+    pub fn end(&self) -> Option<(usize, usize)> {
+        self.path.last().copied()
+    }
+    /// The first actor in the line of fire
+    pub fn first_hit(&self) -> Option<(usize, usize)> {
+        self.hits.first().copied()
+    }
+}
+
+fn is_blocked(tiles: &[Tile], x: isize, y: isize) -> bool {
+    x < 0
+        || y < 0
+        || x >= TILES_HORIZONTAL as isize
+        || y >= TILES_VERTICAL as isize
+        || !tiles[x as usize + y as usize * TILES_HORIZONTAL].is_walkable()
+}
+
+/// Traces a projectile from `from` towards `to` along a bresenham line.
+///
+/// `occupied` tells whether an actor stands on a tile. The projectile stops at `to`,
+/// unless `overshoot` is set, in which case it flies on until something stops it.
+pub fn trace(
+    tiles: &[Tile],
+    occupied: impl Fn((usize, usize)) -> bool,
+    from: (usize, usize),
+    to: (usize, usize),
+    projectile: &Projectile,
+    overshoot: bool,
+) -> Trajectory {
+    let mut trajectory = Trajectory {
+        path: Vec::new(),
+        hits: Vec::new(),
+    };
+    if from == to {
+        return trajectory;
+    }
+    let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+    let (to_x, to_y) = (to.0 as isize, to.1 as isize);
+    let dx = (to_x - x).abs();
+    let dy = -(to_y - y).abs();
+    let mut sx = if to_x > x { 1 } else { -1 };
+    let mut sy = if to_y > y { 1 } else { -1 };
+    let mut err = dx + dy;
+    let mut pierce = projectile.pierce;
+    let mut ricochet = projectile.ricochet;
+
+    while trajectory.path.len() < MAX_DISTANCE {
+        let step_x = 2 * err >= dy;
+        let step_y = 2 * err <= dx;
+        let next_x = if step_x { x + sx } else { x };
+        let next_y = if step_y { y + sy } else { y };
+        if is_blocked(tiles, next_x, next_y) {
+            if ricochet == 0 {
+                break;
+            }
+            ricochet -= 1;
+            // bounce off whichever side of the wall was hit. hitting a corner head on bounces straight back
+            let wall_x = step_x && is_blocked(tiles, next_x, y);
+            let wall_y = step_y && is_blocked(tiles, x, next_y);
+            if wall_x || (step_x && !wall_y) {
+                sx = -sx;
+            }
+            if wall_y || (step_y && !wall_x) {
+                sy = -sy;
+            }
+            continue;
+        }
+        if step_x {
+            err += dy;
+        }
+        if step_y {
+            err += dx;
+        }
+        (x, y) = (next_x, next_y);
+        let pos = (x as usize, y as usize);
+        trajectory.path.push(pos);
+
+        if occupied(pos) {
+            trajectory.hits.push(pos);
+            if pierce == 0 {
+                break;
+            }
+            pierce -= 1;
+        }
+        if !overshoot && pos == to {
+            break;
+        }
+    }
+    trajectory
+}
+
+/// Tiles within `radius` of `center` which aren't shielded from it by walls
+pub fn splash(tiles: &[Tile], center: (usize, usize), radius: f32) -> Vec<(usize, usize)> {
+    let center_pos = vec2(center.0 as f32, center.1 as f32);
+    let mut area = vec![center];
+    for (i, tile) in tiles.iter().enumerate() {
+        let pos = (i % TILES_HORIZONTAL, i / TILES_HORIZONTAL);
+        if pos == center
+            || !tile.is_walkable()
+            || vec2(pos.0 as f32, pos.1 as f32).distance(center_pos) > radius
+        {
+            continue;
+        }
+        if trace(tiles, |_| false, center, pos, &BOLT, false).end() == Some(pos) {
+            area.push(pos);
+        }
+    }
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Tile, ballistics::*};
+
+    /// Builds a map from rows of `#` (wall) and `.` (floor). Everything outside is wall.
+    fn fixture(rows: &[&str]) -> Vec<Tile> {
+        let mut tiles = vec![Tile::Wall; TILES_HORIZONTAL * TILES_VERTICAL];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '.' {
+                    tiles[x + y * TILES_HORIZONTAL] = Tile::Floor;
+                }
+            }
+        }
+        tiles
+    }
+
+    #[test]
+    fn test_line_of_fire() {
+        let tiles = fixture(&["#######", "#.....#", "#..#..#", "#.....#", "#######"]);
+        let clear = trace(&tiles, |_| false, (1, 1), (5, 1), &BOLT, false);
+        assert_eq!(clear.path, vec![(2, 1), (3, 1), (4, 1), (5, 1)]);
+        assert!(clear.hits.is_empty());
+
+        let walled = trace(&tiles, |_| false, (1, 2), (5, 2), &BOLT, false);
+        assert_eq!(walled.end(), Some((2, 2)));
+
+        let overshot = trace(&tiles, |_| false, (1, 3), (2, 3), &BOLT, true);
+        assert_eq!(overshot.end(), Some((5, 3)));
+    }
+
+    #[test]
+    fn test_blocking_and_piercing() {
+        let tiles = fixture(&["#######", "#.....#", "#######"]);
+        let actors = [(3, 1), (4, 1)];
+        let occupied = |pos| actors.contains(&pos);
+
+        let blocked = trace(&tiles, occupied, (1, 1), (5, 1), &BOLT, false);
+        assert_eq!(blocked.first_hit(), Some((3, 1)));
+        assert_eq!(blocked.end(), Some((3, 1)));
+
+        let piercing = Projectile { pierce: 1, ..BOLT };
+        let pierced = trace(&tiles, occupied, (1, 1), (5, 1), &piercing, false);
+        assert_eq!(pierced.hits, vec![(3, 1), (4, 1)]);
+        assert_eq!(pierced.end(), Some((4, 1)));
+    }
+
+    #[test]
+    fn test_ricochet() {
+        let tiles = fixture(&["#####", "#...#", "#####"]);
+        let bouncing = Projectile {
+            ricochet: 1,
+            ..BOLT
+        };
+        let bounced = trace(&tiles, |pos| pos == (1, 1), (2, 1), (3, 1), &bouncing, true);
+        assert_eq!(bounced.path, vec![(3, 1), (2, 1), (1, 1)]);
+        assert_eq!(bounced.first_hit(), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_splash() {
+        let tiles = fixture(&["#####", "#.#.#", "#...#", "#####"]);
+        let mut area = splash(&tiles, (1, 2), 2.0);
+        area.sort();
+        assert_eq!(area, vec![(1, 1), (1, 2), (2, 2), (3, 2)]);
+    }
+}
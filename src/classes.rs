@@ -0,0 +1,71 @@
+use crate::{
+    entities::{Attributes, QUIVER_SLOT},
+    items::*,
+};
+
+/// Passive bonus a class has for the whole run
+#[derive(Clone, Copy, PartialEq)]
+pub enum Trait {
+    /// Takes one less damage from hits
+    Tough,
+    /// Fired arrows are more likely to be recovered
+    Fletcher,
+    /// Knows what every potion is from the start
+    PotionLore,
+}
+pub struct Class {
+    pub name: &'static str,
+    pub desc: &'static str,
+    /// Items the class starts with, as (inventory slot, item, stack size)
+    pub kit: &'static [(usize, Item, u16)],
+    pub attributes: Attributes,
+    pub passive: Trait,
+}
+pub const WARRIOR: Class = Class {
+    name: "warrior",
+    desc: "sturdy fighter.\ntakes less damage",
+    kit: &[
+        (0, Item::Weapon(&STONE_SPEAR), 1),
+        (1, Item::Armor(&TUNIC), 1),
+    ],
+    attributes: Attributes {
+        strength: 1,
+        dexterity: 0,
+        vitality: 1,
+    },
+    passive: Trait::Tough,
+};
+pub const RANGER: Class = Class {
+    name: "ranger",
+    desc: "fights from afar.\nrecovers more arrows",
+    kit: &[
+        (0, Item::Weapon(&SHORTBOW), 1),
+        (2, Item::Weapon(&DAGGER), 1),
+        (QUIVER_SLOT, Item::Ammo(&STONE_ARROW), 12),
+    ],
+    attributes: Attributes {
+        strength: 0,
+        dexterity: 2,
+        vitality: 0,
+    },
+    passive: Trait::Fletcher,
+};
+pub const ALCHEMIST: Class = Class {
+    name: "alchemist",
+    desc: "poisons foes.\nknows all potions",
+    kit: &[
+        (0, Item::Weapon(&DAGGER), 1),
+        (2, Item::Misc(&POISON_ROCK), 1),
+        (3, Item::Misc(&POISON_ROCK), 1),
+        (4, Item::Misc(&POISON_MUSHROOM), 1),
+        (5, Item::Misc(&STONE), 1),
+        (6, Item::Potion(&HEALING_POTION), 1),
+    ],
+    attributes: Attributes {
+        strength: 0,
+        dexterity: 1,
+        vitality: 0,
+    },
+    passive: Trait::PotionLore,
+};
+pub static CLASSES: &[Class] = &[WARRIOR, RANGER, ALCHEMIST];
@@ -3,7 +3,8 @@ use std::iter::Map;
 
 use crate::Tile;
 use crate::entities::*;
-use crate::items::Item;
+use crate::events::{DamageSource, GameEvent};
+use crate::items::{ItemInstance, Rarity};
 use crate::loot::*;
 use crate::particles::Particle;
 use crate::particles::ScreenParticle;
@@ -30,6 +31,8 @@ pub struct DungeonFloor {
     pub get_sprite: &'static dyn Fn(&Tile) -> (f32, f32),
     pub per_room_fn: PerRoomFn,
     pub post_gen_fn: PostGenFn,
+    /// Weights of the rarities that loot found on this floor rolls
+    pub loot_rarity: &'static [(f32, Rarity)],
 }
 
 pub struct Dungeon {
@@ -38,8 +41,10 @@ pub struct Dungeon {
     pub enemies: Vec<Enemy>,
     pub particles: Vec<Box<dyn Particle>>,
     pub screen_particles: Vec<Box<dyn ScreenParticle>>,
-    pub items: Vec<(usize, usize, Item)>,
+    pub items: Vec<(usize, usize, ItemInstance)>,
     pub dungeon_floor: &'static DungeonFloor,
+    /// Whether the player and enemies can move diagonally. Carried over between floors.
+    pub eight_way: bool,
 }
 impl Dungeon {
     pub fn load_from_file(image: Image) -> Self {
@@ -98,6 +103,7 @@ impl Dungeon {
             dungeon_floor: &FIRST_FLOOR,
             particles: Vec::new(),
             screen_particles: Vec::new(),
+            eight_way: false,
         }
     }
     pub fn generate_dungeon(dungeon_floor: &'static DungeonFloor) -> Self {
@@ -193,11 +199,75 @@ impl Dungeon {
             dungeon_floor,
             particles: Vec::new(),
             screen_particles: Vec::new(),
+            eight_way: false,
         };
         (dungeon_floor.post_gen_fn)(&mut dungeon);
         dungeon
     }
 
+    /// Rolls an item from a loot table, with rarity based on the floor's loot rarity weights.
+    pub fn roll_loot(&self, loot_table: &LootTable) -> Option<ItemInstance> {
+        let item = loot_table.get_item()?;
+        let rarity = *weighted_choice(self.dungeon_floor.loot_rarity);
+        Some(ItemInstance::roll(*item, rarity))
+    }
+    /// Drops loot where enemies die
+    pub fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::EnemyKilled { enemy, pos } = event
+            && let Some(loot_table) = enemy.death_drops
+            && let Some(item) = self.roll_loot(loot_table)
+        {
+            self.items.push((pos.0, pos.1, item));
+        }
+    }
+    /// Pushes the enemy at `pos` up to `force` tiles in `dir`, along with any enemies standing in the way.
+    ///
+    /// Enemies slammed into a wall or into `blocker` stop there and take [`SLAM_DAMAGE`].
+    pub fn knockback(
+        &mut self,
+        pos: (usize, usize),
+        dir: (isize, isize),
+        force: usize,
+        blocker: (usize, usize),
+    ) {
+        // moved out so `step` can borrow the dungeon while enemies are moved
+        let mut enemies = std::mem::take(&mut self.enemies);
+        // pushes follow the same rules as walking, so enemies can't be pushed around corners
+        let step = |(x, y): (usize, usize)| {
+            let next = (x.checked_add_signed(dir.0)?, y.checked_add_signed(dir.1)?);
+            (self.can_step((x, y), dir) && next != blocker).then_some(next)
+        };
+        let mut pos = pos;
+        for _ in 0..force {
+            let mut chain = Vec::new();
+            let mut front = Some(pos);
+            while let Some(next) = front
+                && let Some(i) = enemies.iter().position(|f| (f.x, f.y) == next)
+            {
+                chain.push(i);
+                front = step(next);
+            }
+            if chain.is_empty() {
+                break;
+            }
+            if front.is_none() {
+                for i in chain {
+                    enemies[i].damage_undodgeable(SLAM_DAMAGE, DamageSource::Slam);
+                }
+                break;
+            }
+            for i in chain {
+                let enemy = &mut enemies[i];
+                if !matches!(enemy.current_action, Some(EnemyAction::Pushed(_))) {
+                    enemy.current_action = Some(EnemyAction::Pushed(enemy.draw_pos));
+                }
+                (enemy.x, enemy.y) = step((enemy.x, enemy.y)).unwrap_or((enemy.x, enemy.y));
+                enemy.awaken();
+            }
+            pos = step(pos).unwrap();
+        }
+        self.enemies = enemies;
+    }
     pub fn pathfind(
         &self,
         from: (usize, usize),
@@ -210,16 +280,64 @@ impl Dungeon {
             |&p| p == to,
         )
     }
-    fn generate_successors(&self, pos: (usize, usize)) -> SuccessorIterator {
-        let (x, y) = pos;
-        let mut candidates = vec![(x + 1, y), (x, y + 1)];
-        if x > 0 {
-            candidates.push((x - 1, y));
+    /// Like [`Dungeon::pathfind`], but only walks through tiles the player has seen,
+    /// and goes to whichever of `targets` is closest. The path includes `from`.
+    pub fn pathfind_known(
+        &self,
+        from: (usize, usize),
+        targets: &[(usize, usize)],
+        tile_status: &[TileStatus],
+    ) -> Option<Vec<(usize, usize)>> {
+        if targets.is_empty() {
+            return None;
         }
-        if y > 0 {
-            candidates.push((x, y - 1));
+        pathfinding::prelude::bfs(
+            &from,
+            |p| {
+                self.generate_successors(*p)
+                    .filter(|((x, y), _)| !tile_status[x + y * TILES_HORIZONTAL].is_unknown())
+                    .map(|(p, _)| p)
+            },
+            |p| targets.contains(p),
+        )
+    }
+    /// Whether a single step in `dir` from `pos` is allowed. Diagonal steps need eight-way movement,
+    /// and can't cut the corner of a wall.
+    pub fn can_step(&self, pos: (usize, usize), dir: (isize, isize)) -> bool {
+        let walkable = |dx: isize, dy: isize| {
+            let (Some(x), Some(y)) = (pos.0.checked_add_signed(dx), pos.1.checked_add_signed(dy))
+            else {
+                return false;
+            };
+            x < TILES_HORIZONTAL
+                && y < TILES_VERTICAL
+                && self.tiles[x + y * TILES_HORIZONTAL].is_walkable()
+        };
+        match dir {
+            (0, 0) => false,
+            (dx, dy) if dx.abs() > 1 || dy.abs() > 1 => false,
+            (dx, dy) if dx != 0 && dy != 0 => {
+                self.eight_way && walkable(dx, dy) && walkable(dx, 0) && walkable(0, dy)
+            }
+            (dx, dy) => walkable(dx, dy),
         }
-        candidates.retain(|(cx, cy)| self.tiles[cx + cy * TILES_HORIZONTAL].is_walkable());
+    }
+    fn generate_successors(&self, pos: (usize, usize)) -> SuccessorIterator {
+        let (x, y) = pos;
+        let candidates: Vec<(usize, usize)> = [
+            (1, 0),
+            (0, 1),
+            (-1, 0),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ]
+        .into_iter()
+        .filter(|dir| self.can_step(pos, *dir))
+        .map(|(dx, dy)| (x.wrapping_add_signed(dx), y.wrapping_add_signed(dy)))
+        .collect();
         fn map_function(p: (usize, usize)) -> ((usize, usize), usize) {
             (p, 1)
         }
@@ -280,7 +398,12 @@ pub fn is_all_rooms_connected(tiles: &[Tile]) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Tile, dungeon::is_all_rooms_connected, utils::*};
+    use crate::{
+        Tile,
+        dungeon::{DUNGEON_FLOORS, Dungeon, is_all_rooms_connected},
+        entities::{BAT, Enemy, TileStatus},
+        utils::*,
+    };
 
     #[test]
     fn test_rooms_connected() {
@@ -289,4 +412,120 @@ mod tests {
         tiles[1] = Tile::Floor;
         assert!(is_all_rooms_connected(&tiles))
     }
+
+    #[test]
+    fn test_pathfind_known() {
+        let mut tiles = vec![Tile::Wall; TILES_HORIZONTAL * TILES_VERTICAL];
+        // a short way through (2, 2) and a long way around it
+        for (x, y) in [
+            (1, 2),
+            (2, 2),
+            (3, 2),
+            (1, 1),
+            (1, 0),
+            (2, 0),
+            (3, 0),
+            (3, 1),
+        ] {
+            tiles[x + y * TILES_HORIZONTAL] = Tile::Floor;
+        }
+        let dungeon = Dungeon {
+            tiles,
+            player_spawn: (1, 2),
+            enemies: Vec::new(),
+            particles: Vec::new(),
+            screen_particles: Vec::new(),
+            items: Vec::new(),
+            dungeon_floor: &DUNGEON_FLOORS[0],
+            eight_way: false,
+        };
+        let mut tile_status = vec![TileStatus::Remembered; TILES_HORIZONTAL * TILES_VERTICAL];
+        let path = dungeon
+            .pathfind_known((1, 2), &[(3, 2)], &tile_status)
+            .unwrap();
+        assert_eq!(path.len(), 3);
+        // the closest of several targets is picked
+        let path = dungeon
+            .pathfind_known((1, 2), &[(3, 0), (3, 2), (2, 0)], &tile_status)
+            .unwrap();
+        assert_eq!(path.last(), Some(&(3, 2)));
+
+        tile_status[2 + 2 * TILES_HORIZONTAL] = TileStatus::Unknown;
+        let path = dungeon
+            .pathfind_known((1, 2), &[(3, 2)], &tile_status)
+            .unwrap();
+        assert_eq!(path.len(), 7);
+        assert!(dungeon.pathfind_known((1, 2), &[], &tile_status).is_none());
+        assert!(dungeon.pathfind((1, 2), (3, 2)).is_some_and(|f| f.1 == 2));
+    }
+
+    #[test]
+    fn test_eight_way() {
+        let mut tiles = vec![Tile::Wall; TILES_HORIZONTAL * TILES_VERTICAL];
+        // a 3x3 room from (1, 1) to (3, 3)
+        for x in 1..4 {
+            for y in 1..4 {
+                tiles[x + y * TILES_HORIZONTAL] = Tile::Floor;
+            }
+        }
+        let mut dungeon = Dungeon {
+            tiles,
+            player_spawn: (1, 1),
+            enemies: Vec::new(),
+            particles: Vec::new(),
+            screen_particles: Vec::new(),
+            items: Vec::new(),
+            dungeon_floor: &DUNGEON_FLOORS[0],
+            eight_way: false,
+        };
+        assert!(!dungeon.can_step((1, 1), (1, 1)));
+        assert_eq!(dungeon.pathfind((1, 1), (3, 3)).unwrap().1, 4);
+
+        dungeon.eight_way = true;
+        assert!(dungeon.can_step((1, 1), (1, 1)));
+        assert_eq!(dungeon.pathfind((1, 1), (3, 3)).unwrap().1, 2);
+
+        // a pillar in the middle can't be cut around diagonally
+        dungeon.tiles[2 + 2 * TILES_HORIZONTAL] = Tile::Wall;
+        assert!(!dungeon.can_step((1, 2), (1, 1)));
+        assert_eq!(dungeon.pathfind((1, 1), (3, 3)).unwrap().1, 4);
+    }
+
+    #[test]
+    fn test_knockback() {
+        let mut tiles = vec![Tile::Wall; TILES_HORIZONTAL * TILES_VERTICAL];
+        for x in 1..6 {
+            tiles[x + TILES_HORIZONTAL] = Tile::Floor;
+        }
+        tiles[6 + 2 * TILES_HORIZONTAL] = Tile::Floor;
+        let mut dungeon = Dungeon {
+            tiles,
+            player_spawn: (1, 1),
+            enemies: vec![Enemy::new(2, 1, &BAT), Enemy::new(3, 1, &BAT)],
+            particles: Vec::new(),
+            screen_particles: Vec::new(),
+            items: Vec::new(),
+            dungeon_floor: &DUNGEON_FLOORS[0],
+            eight_way: false,
+        };
+        // pushing the first enemy chains into the second, until the second hits the wall
+        dungeon.knockback((2, 1), (1, 0), 3, (1, 1));
+        let positions: Vec<_> = dungeon.enemies.iter().map(|f| (f.x, f.y)).collect();
+        assert_eq!(positions, vec![(4, 1), (5, 1)]);
+        assert!(
+            dungeon
+                .enemies
+                .iter()
+                .all(|f| f.health == BAT.max_health - SLAM_DAMAGE)
+        );
+
+        // a diagonal push can't squeeze between two walls
+        dungeon.eight_way = true;
+        dungeon.knockback((5, 1), (1, 1), 1, (4, 1));
+        assert_eq!((dungeon.enemies[1].x, dungeon.enemies[1].y), (5, 1));
+        assert_eq!(
+            dungeon.enemies[1].health,
+            BAT.max_health - SLAM_DAMAGE * 2.0
+        );
+    }
 }
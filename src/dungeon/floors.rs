@@ -12,6 +12,7 @@ pub const FIRST_FLOOR: DungeonFloor = DungeonFloor {
     from_file: None,
     rooms_area: 5 * 5 * 5,
     get_sprite: &get_tile,
+    loot_rarity: &[(10.0, Rarity::Common), (1.0, Rarity::Uncommon)],
     per_room_fn: &|x: usize, y: usize, w: usize, h: usize, _, enemies| {
         if rand::gen_range(0, 5) < 3 {
             enemies.push(Enemy::new(
@@ -43,7 +44,15 @@ pub const FIRST_FLOOR: DungeonFloor = DungeonFloor {
         for _ in 0..rand::gen_range(3, 6) {
             let i = walkables[rand::gen_range(0, walkables.len())].0;
             let (x, y) = (i % TILES_HORIZONTAL, i / TILES_HORIZONTAL);
-            dungeon.items.push((x, y, Item::Misc(&STONE)));
+            dungeon.items.push((x, y, Item::Misc(&STONE).into()));
+        }
+        // place potions and scrolls
+        for _ in 0..rand::gen_range(0, 3) {
+            let i = walkables[rand::gen_range(0, walkables.len())].0;
+            let (x, y) = (i % TILES_HORIZONTAL, i / TILES_HORIZONTAL);
+            if let Some(item) = dungeon.roll_loot(&CONSUMABLE_LOOT) {
+                dungeon.items.push((x, y, item));
+            }
         }
         place_random_door(dungeon);
     },
@@ -77,6 +86,11 @@ pub const SECOND_FLOOR: DungeonFloor = DungeonFloor {
         }
         (FIRST_FLOOR.post_gen_fn)(dungeon);
     },
+    loot_rarity: &[
+        (6.0, Rarity::Common),
+        (2.0, Rarity::Uncommon),
+        (0.5, Rarity::Rare),
+    ],
     ..FIRST_FLOOR
 };
 pub const THIRD_FLOOR: DungeonFloor = DungeonFloor {
@@ -108,6 +122,12 @@ pub const THIRD_FLOOR: DungeonFloor = DungeonFloor {
         }
         (FIRST_FLOOR.post_gen_fn)(dungeon);
     },
+    loot_rarity: &[
+        (4.0, Rarity::Common),
+        (3.0, Rarity::Uncommon),
+        (1.0, Rarity::Rare),
+        (0.1, Rarity::Legendary),
+    ],
     ..FIRST_FLOOR
 };
 pub const FOURTH_FLOOR: DungeonFloor = DungeonFloor {
@@ -148,6 +168,12 @@ pub const FOURTH_FLOOR: DungeonFloor = DungeonFloor {
         }
         (FIRST_FLOOR.post_gen_fn)(dungeon);
     },
+    loot_rarity: &[
+        (2.0, Rarity::Common),
+        (3.0, Rarity::Uncommon),
+        (2.0, Rarity::Rare),
+        (0.5, Rarity::Legendary),
+    ],
     ..FIRST_FLOOR
 };
 pub const FIFTH_FLOOR: DungeonFloor = DungeonFloor {
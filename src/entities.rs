@@ -1,18 +1,33 @@
 use std::{collections::HashMap, f32::consts::PI, sync::LazyLock};
 
 use crate::{
-    GameState, Tile, assets,
+    GameState, Tile,
+    abilities::*,
+    assets,
+    ballistics::{BOLT, splash, trace},
+    classes::{Class, Trait},
     dungeon::Dungeon,
+    events::{self, DamageSource, GameEvent, Target},
+    input::{self, ABILITY_ACTIONS, Action},
     items::*,
+    log::MessageLog,
     loot::*,
-    particles::{ProjectileParticle, ScreenParticle, TextParticle},
+    particles::{ProjectileParticle, TextParticle, TileFlashParticle},
+    stats::RunStats,
     utils::*,
 };
 use macroquad::prelude::*;
 
+/// Inventory slot holding the ammo used by bows
+pub const QUIVER_SLOT: usize = 14;
+
 pub enum PlayerAction {
     MoveDirection(Vec2),
     Attack(Vec2),
+    /// Pushing an adjacent enemy away
+    Shove(Vec2),
+    /// Dashing several tiles at once, from the stored draw position
+    Dash(Vec2),
     Wait,
     GotoNextDungeon,
 }
@@ -27,6 +42,82 @@ impl TileStatus {
         matches!(self, TileStatus::Unknown)
     }
 }
+#[derive(Clone, Copy, PartialEq)]
+pub enum Attribute {
+    Strength,
+    Dexterity,
+    Vitality,
+}
+#[derive(Clone, Copy, Default)]
+pub struct Attributes {
+    pub strength: u32,
+    pub dexterity: u32,
+    pub vitality: u32,
+}
+impl Attributes {
+    pub fn get(&self, attribute: Attribute) -> u32 {
+        match attribute {
+            Attribute::Strength => self.strength,
+            Attribute::Dexterity => self.dexterity,
+            Attribute::Vitality => self.vitality,
+        }
+    }
+}
+/// How hungry the player is, worsening as nutrition runs out
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum HungerState {
+    Satiated,
+    Hungry,
+    /// Deals less damage and can't dodge
+    Weak,
+    /// Like weak, but also slowly loses health
+    Starving,
+}
+impl HungerState {
+    pub fn name(self) -> &'static str {
+        match self {
+            HungerState::Satiated => "satiated",
+            HungerState::Hungry => "hungry",
+            HungerState::Weak => "weak",
+            HungerState::Starving => "starving",
+        }
+    }
+    /// Damage taken off the player's attacks
+    pub fn damage_penalty(self) -> f32 {
+        match self {
+            HungerState::Satiated => 0.0,
+            HungerState::Hungry => 0.5,
+            HungerState::Weak | HungerState::Starving => 1.0,
+        }
+    }
+}
+/// Places the player can quickly travel back to
+#[derive(Clone, Copy, PartialEq)]
+pub enum Landmark {
+    /// The door leading down to the next floor
+    Exit,
+    /// Unopened chests
+    Chest,
+    Ore,
+    /// Items lying on the floor
+    Item,
+}
+impl Landmark {
+    pub fn name(self) -> &'static str {
+        match self {
+            Landmark::Exit => "exit",
+            Landmark::Chest => "chest",
+            Landmark::Ore => "ore",
+            Landmark::Item => "item",
+        }
+    }
+}
+pub const LANDMARK_ACTIONS: &[(Action, Landmark)] = &[
+    (Action::TravelExit, Landmark::Exit),
+    (Action::TravelChest, Landmark::Chest),
+    (Action::TravelOre, Landmark::Ore),
+    (Action::TravelItem, Landmark::Item),
+];
 pub struct Player {
     pub active_action: Option<PlayerAction>,
     pub moving_to: Vec<(usize, usize)>,
@@ -36,19 +127,58 @@ pub struct Player {
     pub camera_pos: Vec2,
     pub camera_zoom: f32,
     pub tile_status: Vec<TileStatus>,
-    pub inventory: Vec<Option<Item>>,
+    pub inventory: Vec<Option<ItemInstance>>,
     pub health: f32,
     pub was_damaged: bool,
     pub should_throw_item: Option<(usize, Vec2)>,
     pub should_drop_item: Option<usize>,
+    pub should_salvage_item: Option<usize>,
+    /// Scroll to read, and the tile it's aimed at for scrolls that need a target
+    pub should_read_scroll: Option<(usize, Option<Vec2>)>,
+    /// Set when the player jumps somewhere the camera should follow
+    pub should_center_camera: bool,
     pub enemies_slayed: u32,
+    pub xp: u32,
+    pub level: u32,
+    pub attributes: Attributes,
+    /// Level ups not yet spent on attributes
+    pub attribute_points: u32,
     pub status_effects: HashMap<StatusEffect, u16>,
+    pub identification: Identification,
+    /// Abilities unlocked through progression, rather than granted by equipment
+    pub learned_abilities: Vec<&'static Ability>,
+    /// Turns left until each ability can be used again, by name
+    pub cooldowns: HashMap<&'static str, u16>,
+    /// Ability waiting for the player to pick a direction
+    pub aiming_ability: Option<&'static Ability>,
+    pub class: &'static Class,
+    /// Counts down every turn, see [`HungerState`]
+    pub nutrition: u16,
+    /// Turns taken over the whole run
+    pub turns: u32,
+    /// Turns left to keep resting for, see [`Player::regen_rate`]
+    pub resting: Option<u16>,
+    /// Set while auto-exploring, holding the amount of items and chests seen so far
+    pub exploring: Option<usize>,
+    /// Whether auto-explore picks up the items it comes across
+    pub auto_pickup: bool,
+    /// Set while walking to a landmark, see [`Landmark`]
+    pub travelling: bool,
+    /// Tile picked with the keyboard targeting cursor, while it's open
+    pub target_cursor: Option<(usize, usize)>,
+    pub log: MessageLog,
+    pub stats: RunStats,
 }
-impl Default for Player {
-    fn default() -> Self {
-        let mut inventory = vec![None; 14];
-        inventory[0] = Some(Item::Weapon(&DAGGER));
-        Self {
+impl Player {
+    /// Creates a player starting with the kit and stats of `class`
+    pub fn new(class: &'static Class) -> Self {
+        let mut inventory = vec![None; QUIVER_SLOT + 1];
+        for (slot, item, count) in class.kit {
+            let mut item = ItemInstance::new(*item);
+            item.count = *count;
+            inventory[*slot] = Some(item);
+        }
+        let mut player = Self {
             active_action: None,
             moving_to: Vec::new(),
             x: 0,
@@ -58,19 +188,51 @@ impl Default for Player {
             camera_zoom: 1.0,
             tile_status: vec![TileStatus::Unknown; TILES_HORIZONTAL * TILES_VERTICAL],
             inventory,
-            health: MAX_PLAYER_HP,
+            health: BASE_PLAYER_HP,
             was_damaged: false,
             should_throw_item: None,
             should_drop_item: None,
+            should_salvage_item: None,
+            should_read_scroll: None,
+            should_center_camera: false,
             enemies_slayed: 0,
+            xp: 0,
+            level: 1,
+            attributes: class.attributes,
+            attribute_points: 0,
             status_effects: HashMap::new(),
+            identification: Identification::default(),
+            learned_abilities: vec![&DASH],
+            cooldowns: HashMap::new(),
+            aiming_ability: None,
+            class,
+            nutrition: MAX_NUTRITION,
+            turns: 0,
+            resting: None,
+            exploring: None,
+            auto_pickup: true,
+            travelling: false,
+            target_cursor: None,
+            log: MessageLog::default(),
+            stats: RunStats::default(),
+        };
+        player.health = player.max_health();
+        if class.passive == Trait::PotionLore {
+            for potion in POTIONS.iter() {
+                player.identification.identify(Item::Potion(potion));
+            }
         }
+        player
+    }
+    pub fn has_trait(&self, passive: Trait) -> bool {
+        self.class.passive == passive
     }
-}
-impl Player {
     pub fn has_pickaxe(&self) -> bool {
         for item in &self.inventory {
-            if let Some(Item::Weapon(item)) = item
+            if let Some(ItemInstance {
+                item: Item::Weapon(item),
+                ..
+            }) = item
                 && *item == &STONE_AXE
             {
                 return true;
@@ -79,26 +241,382 @@ impl Player {
         false
     }
     pub fn consume(&mut self, index: usize) {
-        if let Some(Item::Misc(item)) = self.inventory[index].take()
-            && let Some((heal, status)) = &item.consumable
+        match self.inventory[index].take().map(|f| f.item) {
+            Some(Item::Misc(MiscItem {
+                consumable: Some((heal, status)),
+                nutrition,
+                ..
+            })) => {
+                self.heal(*heal);
+                self.nutrition = (self.nutrition + nutrition).min(MAX_NUTRITION);
+                if let Some(status) = status {
+                    self.apply_status(*status);
+                }
+            }
+            Some(item @ Item::Potion(potion)) => {
+                self.identification.identify(item);
+                match potion.effect {
+                    PotionEffect::Heal(heal) => {
+                        self.heal(heal);
+                    }
+                    PotionEffect::Status(status) => self.apply_status(status),
+                    PotionEffect::Cure => self.status_effects.clear(),
+                }
+            }
+            _ => panic!("invalid consumable"),
+        }
+    }
+    /// Reads (and uses up) a scroll, identifying it. Returns the effect the scroll has.
+    pub fn read_scroll(&mut self, index: usize) -> ScrollEffect {
+        let Some(item @ Item::Scroll(scroll)) = self.inventory[index].take().map(|f| f.item) else {
+            panic!("invalid scroll");
+        };
+        self.identification.identify(item);
+        scroll.effect
+    }
+    /// Reads a scroll and applies its effect to the dungeon, aimed at `target` if the scroll needs one.
+    ///
+    /// Aimed scrolls with an invalid target fizzle without being used up.
+    fn cast_scroll(
+        &mut self,
+        index: usize,
+        target: Option<Vec2>,
+        dungeon: &mut Dungeon,
+    ) -> Option<PlayerAction> {
+        let Some(ItemInstance {
+            item: Item::Scroll(scroll),
+            ..
+        }) = &self.inventory[index]
+        else {
+            panic!("invalid scroll");
+        };
+        let self_pos = vec2(self.x as f32, self.y as f32);
+        let target = target.unwrap_or(self_pos);
+        let (target_x, target_y) = (target.x as usize, target.y as usize);
+        let target_in_bounds = target.x >= 0.0
+            && target.y >= 0.0
+            && target_x < TILES_HORIZONTAL
+            && target_y < TILES_VERTICAL;
+        if let Some(range) = scroll.effect.range()
+            && !(target_in_bounds
+                && self_pos.distance(target) <= range
+                && matches!(
+                    self.tile_status[target_x + target_y * TILES_HORIZONTAL],
+                    TileStatus::Known
+                )
+                && if let ScrollEffect::Blink { .. } = scroll.effect {
+                    dungeon.tiles[target_x + target_y * TILES_HORIZONTAL].is_walkable()
+                        && !dungeon
+                            .enemies
+                            .iter()
+                            .any(|f| (f.x, f.y) == (target_x, target_y))
+                } else {
+                    self.can_reach((target_x, target_y), dungeon, true)
+                })
         {
-            self.health = (self.health + heal).min(MAX_PLAYER_HP);
+            let text = if let ScrollEffect::Blink { .. } = scroll.effect {
+                "Can't blink there"
+            } else {
+                "Can't aim there"
+            };
+            dungeon.screen_particles.push(Box::new(TextParticle {
+                text: String::from(text),
+                origin: self.draw_pos - vec2(0.0, 4.0),
+                color: WHITE,
+            }));
+            return None;
+        }
 
-            if let Some(status) = status {
-                if let Some(e) = self.status_effects.get_mut(status) {
-                    *e += 3;
+        let effect = self.read_scroll(index);
+        match effect {
+            ScrollEffect::Identify => None,
+            ScrollEffect::Teleport => {
+                let free: Vec<usize> = (0..dungeon.tiles.len())
+                    .filter(|i| {
+                        matches!(dungeon.tiles[*i], Tile::Floor)
+                            && !dungeon
+                                .enemies
+                                .iter()
+                                .any(|f| f.x + f.y * TILES_HORIZONTAL == *i)
+                    })
+                    .collect();
+                if free.is_empty() {
+                    return Some(PlayerAction::Wait);
+                }
+                let i = free[rand::gen_range(0, free.len())];
+                self.move_to((i % TILES_HORIZONTAL, i / TILES_HORIZONTAL), dungeon);
+                self.should_center_camera = true;
+                self.moving_to.clear();
+                Some(PlayerAction::Wait)
+            }
+            ScrollEffect::MagicMapping => {
+                for tile in self.tile_status.iter_mut() {
+                    if tile.is_unknown() {
+                        *tile = TileStatus::Remembered;
+                    }
+                }
+                Some(PlayerAction::Wait)
+            }
+            ScrollEffect::Fireball { radius, .. } | ScrollEffect::Freeze { radius, .. } => {
+                let color = if let ScrollEffect::Fireball { .. } = effect {
+                    ORANGE
                 } else {
-                    self.status_effects.insert(*status, 3);
+                    SKYBLUE
+                };
+                dungeon.particles.push(Box::new(ProjectileParticle {
+                    sprite_x: 3.0,
+                    sprite_y: 1.0,
+                    origin: self.draw_pos + 4.0,
+                    dest: target * 8.0 + 4.0,
+                }));
+                let area = splash(&dungeon.tiles, (target_x, target_y), radius);
+                for &(x, y) in &area {
+                    dungeon
+                        .particles
+                        .push(Box::new(TileFlashParticle { x, y, color }));
+                }
+                let mut hit = Vec::new();
+                for enemy in dungeon.enemies.iter_mut() {
+                    if !area.contains(&(enemy.x, enemy.y)) {
+                        continue;
+                    }
+                    match effect {
+                        ScrollEffect::Fireball { damage, .. } => {
+                            // magic can't be dodged
+                            enemy.damage_undodgeable(damage, DamageSource::Player);
+                        }
+                        _ => {
+                            enemy.apply_status(StatusEffect::Frozen);
+                        }
+                    }
+                    hit.push((enemy.x, enemy.y));
                 }
+                if let ScrollEffect::Fireball { knockback, .. } = effect {
+                    // push the outermost enemies first so they don't block the ones behind them
+                    hit.sort_by_key(|(x, y)| {
+                        std::cmp::Reverse(x.abs_diff(target_x) + y.abs_diff(target_y))
+                    });
+                    for pos in hit {
+                        if pos != (target_x, target_y) {
+                            let dir = cardinal_direction((target_x, target_y), pos);
+                            dungeon.knockback(pos, dir, knockback, (self.x, self.y));
+                        }
+                    }
+                }
+                Some(PlayerAction::Attack(
+                    (target - self_pos).normalize_or_zero(),
+                ))
+            }
+            ScrollEffect::Blink { .. } => {
+                for (x, y) in [(self.x, self.y), (target_x, target_y)] {
+                    dungeon.particles.push(Box::new(TileFlashParticle {
+                        x,
+                        y,
+                        color: VIOLET,
+                    }));
+                }
+                self.move_to((target_x, target_y), dungeon);
+                self.moving_to.clear();
+                Some(PlayerAction::Wait)
             }
+        }
+    }
+    /// Returns the indices of all inventory slots with unidentified items
+    pub fn get_unidentified(&self) -> Vec<usize> {
+        self.inventory
+            .iter()
+            .enumerate()
+            .filter_map(|(i, f)| {
+                f.as_ref()
+                    .filter(|f| !self.identification.is_identified(&f.item))
+                    .map(|_| i)
+            })
+            .collect()
+    }
+    pub fn apply_status(&mut self, status: StatusEffect) {
+        events::emit(GameEvent::StatusApplied {
+            target: Target::Player,
+            status,
+        });
+        if let Some(e) = self.status_effects.get_mut(&status) {
+            *e += status.duration();
+        } else {
+            self.status_effects.insert(status, status.duration());
+        }
+    }
+    /// Stats of the equipped weapon, including affixes and attribute bonuses
+    pub fn get_weapon(&self) -> Weapon {
+        let mut weapon = match &self.inventory[0] {
+            Some(item) => item
+                .get_weapon()
+                .expect("non weapon-type item in weapon slot"),
+            None => MELEE,
+        };
+        weapon.base_damage += if weapon.fires_particle.is_some() {
+            self.ranged_bonus()
         } else {
-            panic!("invalid consumable");
+            self.melee_bonus()
+        };
+        // hunger never takes more than half of the damage away
+        weapon.base_damage -= self
+            .hunger_state()
+            .damage_penalty()
+            .min(weapon.base_damage / 2.0);
+        weapon
+    }
+    pub fn max_health(&self) -> f32 {
+        BASE_PLAYER_HP
+            + (self.level - 1) as f32 * HP_PER_LEVEL
+            + self.attributes.vitality as f32 * HP_PER_VITALITY
+    }
+    pub fn heal(&mut self, amount: f32) {
+        self.health = (self.health + amount).min(self.max_health());
+    }
+    /// Health regenerated every turn. Poison and hunger stop regeneration, while some armor and
+    /// the regeneration status speed it up.
+    pub fn regen_rate(&self) -> f32 {
+        if self.status_effects.contains_key(&StatusEffect::Poison)
+            || self.hunger_state() >= HungerState::Weak
+        {
+            return 0.0;
+        }
+        let mut rate = BASE_REGEN;
+        for item in self.inventory[..2].iter().flatten() {
+            rate += item.affixes.iter().map(|f| f.regen).sum::<f32>();
+        }
+        if self.hunger_state() == HungerState::Hungry {
+            rate /= 2.0;
+        }
+        if self
+            .status_effects
+            .contains_key(&StatusEffect::Regeneration)
+        {
+            rate += REGEN_STATUS_RATE;
+        }
+        rate
+    }
+    pub fn melee_bonus(&self) -> f32 {
+        self.attributes.strength as f32 * DAMAGE_PER_ATTRIBUTE
+    }
+    /// Extra damage for ranged attacks and throws
+    pub fn ranged_bonus(&self) -> f32 {
+        self.attributes.dexterity as f32 * DAMAGE_PER_ATTRIBUTE
+    }
+    pub fn dodge_chance(&self) -> f32 {
+        if self.hunger_state() >= HungerState::Weak {
+            return 0.0;
+        }
+        self.attributes.dexterity as f32 * DODGE_PER_DEXTERITY
+    }
+    pub fn hunger_state(&self) -> HungerState {
+        match self.nutrition {
+            0 => HungerState::Starving,
+            n if n < WEAK_NUTRITION => HungerState::Weak,
+            n if n < HUNGRY_NUTRITION => HungerState::Hungry,
+            _ => HungerState::Satiated,
+        }
+    }
+    /// Uses up a turn's worth of nutrition, warning the player when they get hungrier.
+    /// Starving players take damage every few turns.
+    pub fn tick_hunger(&mut self, dungeon: &mut Dungeon) {
+        let old_state = self.hunger_state();
+        self.nutrition = self.nutrition.saturating_sub(1);
+        let state = self.hunger_state();
+        if state != old_state {
+            dungeon.screen_particles.push(Box::new(TextParticle {
+                text: format!("{}!", state.name()),
+                origin: self.draw_pos - vec2(0.0, 4.0),
+                color: ORANGE,
+            }));
+        }
+        if state == HungerState::Starving && self.turns.is_multiple_of(STARVING_DAMAGE_INTERVAL) {
+            self.health -= 1.0;
+            self.was_damaged = true;
+            events::emit(GameEvent::DamageDealt {
+                target: Target::Player,
+                source: DamageSource::Starvation,
+                amount: 1.0,
+                pos: self.draw_pos - vec2(0.0, 4.0),
+            });
+        }
+    }
+    pub fn xp_to_next_level(&self) -> u32 {
+        self.level * XP_PER_LEVEL
+    }
+    /// Adds XP, levelling up as many times as it's enough for. Returns whether the player levelled up.
+    pub fn give_xp(&mut self, xp: u32) -> bool {
+        self.xp += xp;
+        let mut levelled_up = false;
+        while self.xp >= self.xp_to_next_level() {
+            self.xp -= self.xp_to_next_level();
+            self.level += 1;
+            self.attribute_points += 1;
+            self.health += HP_PER_LEVEL;
+            levelled_up = true;
+        }
+        levelled_up
+    }
+    /// Counts kills and hands out their XP
+    pub fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::EnemyKilled { enemy, .. } = event {
+            self.enemies_slayed += 1;
+            if self.give_xp(enemy.xp) {
+                events::emit(GameEvent::LevelUp {
+                    level: self.level,
+                    pos: self.draw_pos - vec2(0.0, 8.0),
+                });
+            }
         }
     }
-    pub fn damage(&mut self, amt: f32, dungeon: &mut Dungeon) {
+    /// Spends an attribute point on raising `attribute`
+    pub fn raise_attribute(&mut self, attribute: Attribute) {
+        if self.attribute_points == 0 {
+            return;
+        }
+        self.attribute_points -= 1;
+        match attribute {
+            Attribute::Strength => self.attributes.strength += 1,
+            Attribute::Dexterity => self.attributes.dexterity += 1,
+            Attribute::Vitality => {
+                self.attributes.vitality += 1;
+                self.health += HP_PER_VITALITY;
+            }
+        }
+    }
+    /// Wears down the item in `slot`, breaking it into salvage when its durability runs out.
+    pub fn wear_item(&mut self, slot: usize, dungeon: &mut Dungeon) {
+        let Some(item) = &mut self.inventory[slot] else {
+            return;
+        };
+        if item.wear() {
+            let item = self.inventory[slot].take().unwrap();
+            events::emit(GameEvent::ItemBroke {
+                name: self.identification.get_name(&item),
+                pos: self.draw_pos - vec2(0.0, 4.0),
+            });
+            for ingredient in salvage(item.item, SALVAGE_LOSS) {
+                self.give_item(ingredient.into(), dungeon);
+            }
+        }
+    }
+    /// Damages the player unless they dodge or their armor blocks it
+    pub fn damage(&mut self, amt: f32, source: DamageSource, dungeon: &mut Dungeon) {
+        let amt = if self.has_trait(Trait::Tough) && amt > 1.0 {
+            amt - 1.0
+        } else {
+            amt
+        };
+        if rand::gen_range(0.0, 1.0) < self.dodge_chance() {
+            events::emit(GameEvent::Dodged {
+                target: Target::Player,
+                blocked: false,
+                pos: self.draw_pos - vec2(0.0, 4.0),
+            });
+            return;
+        }
         let rng = rand::gen_range(0.0, 1.0);
-        if self.inventory[1].is_none_or(|f| {
-            if let Item::Armor(armor) = f {
+        if self.inventory[1].as_ref().is_none_or(|f| {
+            if let Some(armor) = f.get_armor() {
                 armor.block_chance < rng
             } else {
                 panic!()
@@ -106,17 +624,19 @@ impl Player {
         }) {
             self.was_damaged = true;
             self.health -= amt;
-            dungeon.screen_particles.push(Box::new(TextParticle {
-                text: format!("-{amt}"),
-                origin: self.draw_pos - vec2(0.0, 4.0),
-                color: RED,
-            }));
+            events::emit(GameEvent::DamageDealt {
+                target: Target::Player,
+                source,
+                amount: amt,
+                pos: self.draw_pos - vec2(0.0, 4.0),
+            });
         } else {
-            dungeon.screen_particles.push(Box::new(TextParticle {
-                text: String::from("Dodged!"),
-                origin: self.draw_pos - vec2(0.0, 4.0),
-                color: WHITE,
-            }));
+            events::emit(GameEvent::Dodged {
+                target: Target::Player,
+                blocked: true,
+                pos: self.draw_pos - vec2(0.0, 4.0),
+            });
+            self.wear_item(1, dungeon);
         }
     }
     pub fn draw(&self, assets: &assets::Assets, _time_since_start: f64) {
@@ -129,7 +649,11 @@ impl Player {
         if self.was_damaged {
             gl_use_default_material();
         }
-        if let Some(Item::Weapon(item)) = &self.inventory[0] {
+        if let Some(ItemInstance {
+            item: Item::Weapon(item),
+            ..
+        }) = &self.inventory[0]
+        {
             assets.items.draw_tile(
                 self.draw_pos.x - 5.0,
                 self.draw_pos.y - 2.0,
@@ -138,7 +662,11 @@ impl Player {
                 None,
             );
         }
-        if let Some(Item::Armor(item)) = &self.inventory[1] {
+        if let Some(ItemInstance {
+            item: Item::Armor(item),
+            ..
+        }) = &self.inventory[1]
+        {
             assets.items.draw_tile(
                 self.draw_pos.x,
                 self.draw_pos.y,
@@ -190,6 +718,127 @@ impl Player {
         self.get_visible_tiles(dungeon);
         self.reset_draw_pos();
     }
+    /// Whether `tile` is within reach of the equipped weapon
+    pub fn in_weapon_range(&self, tile: (usize, usize), dungeon: &Dungeon) -> bool {
+        let weapon = self.get_weapon();
+        weapon.distance((self.x, self.y), tile, dungeon.eight_way)
+            <= weapon.attack_range.max().unwrap()
+    }
+    /// Whether an attack (or a throw) aimed at `tile` would reach it unobstructed
+    pub fn can_reach(&self, tile: (usize, usize), dungeon: &Dungeon, throwing: bool) -> bool {
+        let trajectory = trace(
+            &dungeon.tiles,
+            |pos| dungeon.enemies.iter().any(|f| (f.x, f.y) == pos),
+            (self.x, self.y),
+            tile,
+            &BOLT,
+            false,
+        );
+        trajectory.end() == Some(tile) && (throwing || self.in_weapon_range(tile, dungeon))
+    }
+    /// Moves a keyboard targeting cursor. The target action cycles through visible enemies,
+    /// nearest first, and the movement keys move the cursor freely.
+    /// A cursor that isn't placed yet starts on the player.
+    pub fn step_target_cursor(
+        &self,
+        cursor: Option<(usize, usize)>,
+        dungeon: &Dungeon,
+    ) -> Option<(usize, usize)> {
+        let here = (self.x, self.y);
+        let mut cursor = cursor;
+        if input::is_pressed(Action::Target) {
+            let mut enemies: Vec<(usize, usize)> = self
+                .get_visible_enemies(dungeon)
+                .iter()
+                .map(|f| (f.x, f.y))
+                .collect();
+            enemies.sort_by_key(|p| p.0.abs_diff(here.0).pow(2) + p.1.abs_diff(here.1).pow(2));
+            let next = cursor
+                .and_then(|c| enemies.iter().position(|f| *f == c))
+                .map_or(0, |i| i + 1);
+            cursor = Some(
+                enemies
+                    .get(next % enemies.len().max(1))
+                    .copied()
+                    .unwrap_or(here),
+            );
+        }
+        if let Some((dx, dy)) = get_pressed_direction() {
+            let (x, y) = cursor.unwrap_or(here);
+            cursor = Some((
+                x.saturating_add_signed(dx).min(TILES_HORIZONTAL - 1),
+                y.saturating_add_signed(dy).min(TILES_VERTICAL - 1),
+            ));
+        }
+        cursor
+    }
+    /// Nearest visible enemy, preferring ones that can be hit from here
+    fn nearest_target(&self, dungeon: &Dungeon) -> Option<(usize, usize)> {
+        self.get_visible_enemies(dungeon)
+            .iter()
+            .map(|f| (f.x, f.y))
+            .min_by_key(|p| {
+                (
+                    !self.can_reach(*p, dungeon, false),
+                    p.0.abs_diff(self.x).pow(2) + p.1.abs_diff(self.y).pow(2),
+                )
+            })
+    }
+    /// Title and details shown when inspecting a tile: the enemy standing there, its items, or the
+    /// tile itself. Remembered tiles only describe what doesn't move.
+    pub fn inspect(
+        &self,
+        tile: (usize, usize),
+        dungeon: &Dungeon,
+    ) -> Option<(String, Vec<String>)> {
+        let status = self.tile_status[tile.0 + tile.1 * TILES_HORIZONTAL];
+        if status.is_unknown() {
+            return None;
+        }
+        let visible = matches!(status, TileStatus::Known);
+        if visible && let Some(enemy) = dungeon.enemies.iter().find(|f| (f.x, f.y) == tile) {
+            let weapon = enemy.ty.weapon;
+            let mut lines = vec![
+                format!("HP: {}/{}", enemy.health.ceil(), enemy.ty.max_health),
+                format!(
+                    "{}, RANGE: {}",
+                    weapon.name.to_uppercase(),
+                    serialize_range(&weapon.attack_range)
+                ),
+            ];
+            for (effect, turns) in &enemy.status_effects {
+                lines.push(format!("{} ({turns})", effect.name().to_uppercase()));
+            }
+            lines.push(String::from(if enemy.awake { "AWAKE" } else { "ASLEEP" }));
+            return Some((enemy.ty.name.to_uppercase(), lines));
+        }
+        let items: Vec<String> = dungeon
+            .items
+            .iter()
+            .filter(|(x, y, _)| visible && (*x, *y) == tile)
+            .map(|(_, _, item)| self.identification.get_name(item).to_uppercase())
+            .collect();
+        let feature = match dungeon.tiles[tile.0 + tile.1 * TILES_HORIZONTAL] {
+            Tile::Chest(..) => Some(("CHEST", "HOLDS LOOT")),
+            Tile::Ore(..) => Some(("ORE VEIN", "MINED WITH A PICKAXE")),
+            Tile::Door => Some(("DOOR", "LEADS TO THE NEXT FLOOR")),
+            _ => None,
+        };
+        let (title, mut lines) = match (feature, items.as_slice()) {
+            (Some((title, desc)), _) => (title.to_string(), vec![desc.to_string()]),
+            (None, []) => return None,
+            (None, [item]) => return Some((item.clone(), Vec::new())),
+            (None, _) => (String::from("ITEMS"), Vec::new()),
+        };
+        lines.extend(items.iter().take(3).cloned());
+        if items.len() > 3 {
+            lines.push(format!("AND {} MORE", items.len() - 3));
+        }
+        if !visible {
+            lines.push(String::from("REMEMBERED"));
+        }
+        Some((title, lines))
+    }
     pub fn get_visible_enemies<'a>(&self, dungeon: &'a Dungeon) -> Vec<&'a Enemy> {
         dungeon
             .enemies
@@ -210,20 +859,47 @@ impl Player {
         click: Option<(usize, usize)>,
     ) -> Option<PlayerAction> {
         // stop moving towards target if any key is pressed
-        if !self.moving_to.is_empty() && !get_keys_pressed().is_empty() {
+        if !self.moving_to.is_empty() && input::any_pressed() {
             self.moving_to = Vec::new();
         }
+        if (self.resting.is_some() || self.exploring.is_some()) && input::any_pressed() {
+            self.resting = None;
+            self.exploring = None;
+            self.moving_to.clear();
+        }
+        // keyboard targeting, confirming with enter acts the same as clicking the tile
+        let mut click = click;
+        if let GameState::Idle = state
+            && (self.target_cursor.is_some() || input::is_pressed(Action::Target))
+        {
+            self.target_cursor = self.step_target_cursor(self.target_cursor, dungeon);
+            if input::is_pressed(Action::Confirm) {
+                click = self.target_cursor.take();
+            } else {
+                if input::is_pressed(Action::Cancel) {
+                    self.target_cursor = None;
+                }
+                return None;
+            }
+        } else if let GameState::Idle = state
+            && click.is_none()
+            && self.aiming_ability.is_none()
+            && input::is_pressed(Action::Confirm)
+        {
+            click = self.nearest_target(dungeon);
+        }
         if let GameState::Idle = state
             && let Some((index, pos)) = self.should_throw_item
         {
             let item = self.inventory[index].take().unwrap();
             self.should_throw_item = None;
+            events::emit(GameEvent::ItemThrown {
+                name: self.identification.get_name(&item),
+            });
             let self_pos = vec2(self.x as f32, self.y as f32);
-            let delta_normalized = (pos - self_pos).normalize();
-            let mut current = self_pos;
-            let max_step = 0.15;
-            let throwable = item.throwable().unwrap();
-            let status_effect = if let Item::Misc(misc) = item {
+            let delta_normalized = (pos - self_pos).normalize_or_zero();
+            let throwable = item.item.throwable().unwrap();
+            let status_effect = if let Item::Misc(misc) = item.item {
                 if let Some(consumable) = &misc.consumable {
                     consumable.1
                 } else {
@@ -232,53 +908,62 @@ impl Player {
             } else {
                 None
             };
-            loop {
-                current += delta_normalized * max_step;
-
-                let (tx, ty) = ((current.x).round() as usize, (current.y).round() as usize);
-                if !dungeon.tiles[tx + ty * TILES_HORIZONTAL].is_walkable() {
-                    current -= delta_normalized * max_step;
-                    let (tx, ty) = ((current.x).round() as usize, (current.y).round() as usize);
+            let trajectory = trace(
+                &dungeon.tiles,
+                |pos| dungeon.enemies.iter().any(|f| (f.x, f.y) == pos),
+                (self.x, self.y),
+                (pos.x.round() as usize, pos.y.round() as usize),
+                &BOLT,
+                true,
+            );
+            let (tx, ty) = trajectory.end().unwrap_or((self.x, self.y));
+            if let Some(enemy) = dungeon.enemies.iter_mut().find(|f| (f.x, f.y) == (tx, ty)) {
+                let damage = if throwable.0 > 0.0 {
+                    throwable.0 + self.ranged_bonus()
+                } else {
+                    0.0
+                };
+                if !enemy.damage_throwing(damage, status_effect) {
                     dungeon.items.push((tx, ty, item));
-                    break;
-                }
-                if let Some(enemy) = dungeon.enemies.iter_mut().find(|f| f.x == tx && f.y == ty) {
-                    if !enemy.damage_throwing(
-                        throwable.0,
-                        status_effect,
-                        &mut dungeon.screen_particles,
-                    ) {
-                        dungeon.items.push((tx, ty, item));
-                    }
-                    break;
                 }
+            } else {
+                dungeon.items.push((tx, ty, item));
             }
 
             dungeon.particles.push(Box::new(ProjectileParticle {
                 sprite_x: throwable.1.x,
                 sprite_y: throwable.1.y,
                 origin: self.draw_pos + 4.0,
-                dest: current * 8.0 + 4.0,
+                dest: vec2(tx as f32 * 8.0 + 4.0, ty as f32 * 8.0 + 4.0),
             }));
             return Some(PlayerAction::Attack(delta_normalized));
         }
+        if let GameState::Idle = state
+            && let Some((index, target)) = self.should_read_scroll.take()
+        {
+            return self.cast_scroll(index, target, dungeon);
+        }
+        if let GameState::Idle = state
+            && let Some(ability) = self.aiming_ability
+            && let Some(tile) = click
+        {
+            let dir = cardinal_direction((self.x, self.y), tile);
+            return self.use_ability(ability, dir, dungeon);
+        }
         if let GameState::Idle = state
             && let Some((tile_x, tile_y)) = click
             && dungeon.tiles[tile_x + tile_y * TILES_HORIZONTAL].is_walkable()
             && !self.tile_status[tile_x + tile_y * TILES_HORIZONTAL].is_unknown()
         {
             let delta = vec2(tile_x as f32 - self.x as f32, tile_y as f32 - self.y as f32);
-            let Item::Weapon(weapon) = &self.inventory[0].unwrap_or(Item::Weapon(&MELEE)) else {
-                panic!("non weapon-type item in weapon slot")
-            };
-            let weapon_in_range =
-                ((delta.length()) as usize) <= weapon.attack_range.clone().max().unwrap();
+            let weapon = self.get_weapon();
+            let weapon_in_range = self.in_weapon_range((tile_x, tile_y), dungeon);
 
             // if we click an enemy which is in range, attack it.
-            if let Some(enemy) = dungeon
+            if dungeon
                 .enemies
-                .iter_mut()
-                .find(|f| (f.x, f.y) == (tile_x, tile_y))
+                .iter()
+                .any(|f| (f.x, f.y) == (tile_x, tile_y))
                 && (weapon_in_range
                     || matches!(
                         self.tile_status[tile_x + tile_y * TILES_HORIZONTAL],
@@ -286,13 +971,107 @@ impl Player {
                     ))
             {
                 if weapon_in_range {
-                    enemy.damage(weapon.base_damage, &mut dungeon.screen_particles);
+                    let mut damage = weapon.base_damage;
+                    let mut on_hit = weapon.on_hit;
+                    let mut projectile = weapon.projectile;
+                    let ammo = self.quiver_ammo();
+                    if weapon.uses_ammo {
+                        let Some(ammo) = ammo else {
+                            dungeon.screen_particles.push(Box::new(TextParticle {
+                                text: String::from("No ammo!"),
+                                origin: self.draw_pos - vec2(0.0, 4.0),
+                                color: WHITE,
+                            }));
+                            return None;
+                        };
+                        damage += ammo.damage;
+                        on_hit = ammo.on_hit.or(on_hit);
+                        projectile.pierce += ammo.pierce;
+                    }
+                    let mut targets = vec![(tile_x, tile_y)];
+                    let mut impact = (tile_x, tile_y);
+                    if weapon.fires_particle.is_some() {
+                        let trajectory = trace(
+                            &dungeon.tiles,
+                            |pos| dungeon.enemies.iter().any(|f| (f.x, f.y) == pos),
+                            (self.x, self.y),
+                            (tile_x, tile_y),
+                            &projectile,
+                            false,
+                        );
+                        if trajectory.hits.is_empty() {
+                            dungeon.screen_particles.push(Box::new(TextParticle {
+                                text: String::from("No line of fire"),
+                                origin: self.draw_pos - vec2(0.0, 4.0),
+                                color: WHITE,
+                            }));
+                            return None;
+                        }
+                        impact = trajectory.end().unwrap();
+                        targets = trajectory.hits;
+                        if projectile.splash > 0.0 {
+                            for pos in splash(&dungeon.tiles, impact, projectile.splash) {
+                                if !targets.contains(&pos) {
+                                    targets.push(pos);
+                                }
+                                dungeon.particles.push(Box::new(TileFlashParticle {
+                                    x: pos.0,
+                                    y: pos.1,
+                                    color: PURPLE,
+                                }));
+                            }
+                        }
+                    }
+                    if weapon.uses_ammo {
+                        self.take_ammo();
+                    }
+                    let mut hit = Vec::new();
+                    for enemy in dungeon
+                        .enemies
+                        .iter_mut()
+                        .filter(|f| targets.contains(&(f.x, f.y)))
+                    {
+                        let hits = enemy.damage(damage);
+                        if hits {
+                            if let Some(status) = on_hit {
+                                enemy.apply_status(status);
+                            }
+                            hit.push((enemy.x, enemy.y));
+                        }
+                    }
+                    if !hit.is_empty() {
+                        self.wear_item(0, dungeon);
+                    }
+                    if weapon.knockback > 0 {
+                        // push the furthest enemies first so they don't block the ones in front
+                        hit.sort_by_key(|(x, y)| {
+                            std::cmp::Reverse(x.abs_diff(self.x) + y.abs_diff(self.y))
+                        });
+                        for pos in hit {
+                            let dir = cardinal_direction((self.x, self.y), pos);
+                            dungeon.knockback(pos, dir, weapon.knockback, (self.x, self.y));
+                        }
+                    }
+                    if weapon.uses_ammo
+                        && let Some(ammo) = ammo
+                        && rand::gen_range(0.0, 1.0)
+                            < ammo.recover_chance
+                                + if self.has_trait(Trait::Fletcher) {
+                                    FLETCHER_RECOVER_BONUS
+                                } else {
+                                    0.0
+                                }
+                    {
+                        dungeon
+                            .items
+                            .push((impact.0, impact.1, Item::Ammo(ammo).into()));
+                    }
                     if let Some(particle) = weapon.fires_particle {
                         dungeon.particles.push(Box::new(ProjectileParticle {
                             sprite_x: particle.0,
                             sprite_y: particle.1,
                             origin: self.draw_pos + 4.0,
-                            dest: vec2(tile_x as f32 * 8.0 + 4.0, tile_y as f32 * 8.0 + 4.0),
+                            dest: vec2(impact.0 as f32 * 8.0 + 4.0, impact.1 as f32 * 8.0 + 4.0),
                         }));
                     }
                     return Some(PlayerAction::Attack(delta.normalize()));
@@ -328,10 +1107,14 @@ impl Player {
                     self.draw_pos += *dir * speed;
                 }
             }
-            PlayerAction::Attack(dir) => {
+            PlayerAction::Attack(dir) | PlayerAction::Shove(dir) => {
                 self.draw_pos = vec2((self.x * 8) as f32, (self.y * 8) as f32);
                 self.draw_pos += *dir * (animation_time / ACTION_TIME * PI).sin() * 3.0;
             }
+            PlayerAction::Dash(origin) => {
+                let target = vec2((self.x * 8) as f32, (self.y * 8) as f32);
+                self.draw_pos = origin.lerp(target, (animation_time / ACTION_TIME).min(1.0));
+            }
             _ => {}
         }
     }
@@ -340,6 +1123,25 @@ impl Player {
     /// Returns whether player performed an action, and subsequently game should let all enemies act.
     pub fn update_idle(&mut self, dungeon: &mut Dungeon, _delta_time: f32) -> Option<PlayerAction> {
         self.reset_draw_pos();
+        if let Some(turns) = self.resting {
+            if turns == 0
+                || self.was_damaged
+                || self.health >= self.max_health()
+                || !self.get_visible_enemies(dungeon).is_empty()
+            {
+                self.resting = None;
+            } else {
+                self.resting = Some(turns - 1);
+                return Some(PlayerAction::Wait);
+            }
+        }
+        self.explore(dungeon);
+        if self.travelling {
+            if self.was_damaged || !self.get_visible_enemies(dungeon).is_empty() {
+                self.moving_to.clear();
+            }
+            self.travelling = !self.moving_to.is_empty();
+        }
         if self.was_damaged {
             self.was_damaged = false;
         }
@@ -350,20 +1152,49 @@ impl Player {
         } else {
             get_input_axis()
         };
-        if input.length() == 1.0 {
+        for (i, ability) in self.get_abilities().into_iter().enumerate() {
+            if !input::is_pressed(ABILITY_ACTIONS[i]) {
+                continue;
+            }
+            if self.aiming_ability == Some(ability) {
+                self.aiming_ability = None;
+            } else if self.cooldowns.contains_key(ability.name) {
+                dungeon.screen_particles.push(Box::new(TextParticle {
+                    text: String::from("Not ready"),
+                    origin: self.draw_pos - vec2(0.0, 4.0),
+                    color: WHITE,
+                }));
+            } else if ability.effect.is_aimed() {
+                self.aiming_ability = Some(ability);
+            } else {
+                return self.use_ability(ability, (0, 0), dungeon);
+            }
+        }
+        if let Some(ability) = self.aiming_ability
+            && input.length() == 1.0
+        {
+            self.moving_to.clear();
+            return self.use_ability(ability, (input.x as isize, input.y as isize), dungeon);
+        }
+        let dir = (input.x as isize, input.y as isize);
+        if dungeon.can_step((self.x, self.y), dir) {
             let new = (
-                self.x.saturating_add_signed(input.x as isize),
-                self.y.saturating_add_signed(input.y as isize),
+                self.x.saturating_add_signed(dir.0),
+                self.y.saturating_add_signed(dir.1),
             );
-            if !dungeon.enemies.iter_mut().any(|f| (f.x, f.y) == new)
-                && dungeon.tiles[new.0 + new.1 * TILES_HORIZONTAL].is_walkable()
-            {
+            let enemy_in_the_way = dungeon.enemies.iter().any(|f| (f.x, f.y) == new);
+            if !enemy_in_the_way {
                 (self.x, self.y) = new;
                 self.get_visible_tiles(dungeon);
                 return Some(PlayerAction::MoveDirection(input));
             }
+            // shift + direction shoves the enemy in the way
+            if input::is_down(Action::Shove) {
+                dungeon.knockback(new, dir, 1, (self.x, self.y));
+                return Some(PlayerAction::Shove(input));
+            }
         }
-        if is_key_pressed(KeyCode::H) {
+        if input::is_pressed(Action::Wait) {
             dungeon.screen_particles.push(Box::new(TextParticle {
                 text: String::from("Wait"),
                 origin: self.draw_pos - vec2(0.0, 4.0),
@@ -371,7 +1202,54 @@ impl Player {
             }));
             return Some(PlayerAction::Wait);
         }
-        if is_key_pressed(KeyCode::E) {
+        if input::is_pressed(Action::Rest) {
+            let reason = if !self.get_visible_enemies(dungeon).is_empty() {
+                "Enemies nearby"
+            } else if self.health >= self.max_health() {
+                "Already healed"
+            } else {
+                self.resting = Some(REST_TURNS);
+                return None;
+            };
+            dungeon.screen_particles.push(Box::new(TextParticle {
+                text: String::from(reason),
+                origin: self.draw_pos - vec2(0.0, 4.0),
+                color: WHITE,
+            }));
+        }
+        if input::is_pressed(Action::Explore) {
+            self.exploring = Some(self.count_points_of_interest(dungeon));
+            return None;
+        }
+        for (action, landmark) in LANDMARK_ACTIONS {
+            if input::is_pressed(*action) {
+                self.travel_to(*landmark, dungeon);
+                return None;
+            }
+        }
+        if input::is_pressed(Action::EightWay) {
+            dungeon.eight_way = !dungeon.eight_way;
+            dungeon.screen_particles.push(Box::new(TextParticle {
+                text: format!(
+                    "Eight-way movement {}",
+                    if dungeon.eight_way { "on" } else { "off" }
+                ),
+                origin: self.draw_pos - vec2(0.0, 4.0),
+                color: WHITE,
+            }));
+        }
+        if input::is_pressed(Action::AutoPickup) {
+            self.auto_pickup = !self.auto_pickup;
+            dungeon.screen_particles.push(Box::new(TextParticle {
+                text: format!(
+                    "Auto pickup {}",
+                    if self.auto_pickup { "on" } else { "off" }
+                ),
+                origin: self.draw_pos - vec2(0.0, 4.0),
+                color: WHITE,
+            }));
+        }
+        if input::is_pressed(Action::Interact) {
             // try interact with current tile
             let tile = &mut dungeon.tiles[self.x + self.y * TILES_HORIZONTAL];
             return match tile {
@@ -379,13 +1257,9 @@ impl Player {
                     let mut buffer = Tile::Detail(*sprite_x + 1.0, *sprite_y);
                     std::mem::swap(&mut buffer, tile);
                     if let Tile::Chest(_, _, loot) = buffer
-                        && let Some(item) = loot.get_item()
+                        && let Some(item) = dungeon.roll_loot(loot)
                     {
-                        if let Some(slot) = self.get_free_slot() {
-                            self.inventory[slot] = Some(*item);
-                        } else {
-                            dungeon.items.push((self.x, self.y, *item));
-                        }
+                        self.give_item(item, dungeon);
                     }
 
                     None
@@ -396,28 +1270,16 @@ impl Player {
                         let mut buffer = Tile::Detail(*sprite_x + 1.0, *sprite_y);
                         std::mem::swap(&mut buffer, tile);
                         if let Tile::Ore(_, _, loot) = buffer
-                            && let Some(item) = loot.get_item()
+                            && let Some(item) = dungeon.roll_loot(loot)
                         {
-                            if let Some(slot) = self.get_free_slot() {
-                                self.inventory[slot] = Some(*item);
-                            } else {
-                                dungeon.items.push((self.x, self.y, *item));
-                            }
+                            self.give_item(item, dungeon);
                         }
                     }
 
                     None
                 }
                 _ => {
-                    if let Some(item) = dungeon
-                        .items
-                        .iter()
-                        .position(|(x, y, _)| (x, y) == (&self.x, &self.y))
-                        && let Some(slot) = self.get_free_slot()
-                    {
-                        let (_, _, item) = dungeon.items.remove(item);
-                        self.inventory[slot] = Some(item);
-                    }
+                    self.pick_up_item(dungeon);
                     None
                 }
             };
@@ -425,14 +1287,355 @@ impl Player {
 
         None
     }
+    /// Picks up an item from the current tile, if there's room for it. Returns whether it did.
+    fn pick_up_item(&mut self, dungeon: &mut Dungeon) -> bool {
+        if let Some(item) = dungeon
+            .items
+            .iter()
+            .position(|(x, y, _)| (x, y) == (&self.x, &self.y))
+            && let Some(slot) = self
+                .get_stack_slot(&dungeon.items[item].2)
+                .or(self.get_free_slot())
+        {
+            let (_, _, item) = dungeon.items.remove(item);
+            events::emit(GameEvent::ItemPickedUp {
+                name: self.identification.get_name(&item),
+                dropped: false,
+            });
+            self.put_item(slot, item);
+            return true;
+        }
+        false
+    }
+    /// Chests the player has seen on this floor, plus items unless auto-explore picks them up
+    fn count_points_of_interest(&self, dungeon: &Dungeon) -> usize {
+        let items = dungeon
+            .items
+            .iter()
+            .filter(|(x, y, _)| {
+                !self.auto_pickup && !self.tile_status[x + y * TILES_HORIZONTAL].is_unknown()
+            })
+            .count();
+        let chests = dungeon
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(i, f)| matches!(f, Tile::Chest(..)) && !self.tile_status[*i].is_unknown())
+            .count();
+        items + chests
+    }
+    /// Seen walkable tiles next to walkable tiles that haven't been seen yet
+    fn unexplored_edges(&self, dungeon: &Dungeon) -> Vec<(usize, usize)> {
+        let is_unexplored = |x: usize, y: usize| {
+            x < TILES_HORIZONTAL
+                && y < TILES_VERTICAL
+                && self.tile_status[x + y * TILES_HORIZONTAL].is_unknown()
+                && dungeon.tiles[x + y * TILES_HORIZONTAL].is_walkable()
+        };
+        let mut edges = Vec::new();
+        for (i, status) in self.tile_status.iter().enumerate() {
+            let (x, y) = (i % TILES_HORIZONTAL, i / TILES_HORIZONTAL);
+            if !status.is_unknown()
+                && dungeon.tiles[i].is_walkable()
+                && (is_unexplored(x + 1, y)
+                    || is_unexplored(x, y + 1)
+                    || (x > 0 && is_unexplored(x - 1, y))
+                    || (y > 0 && is_unexplored(x, y - 1)))
+            {
+                edges.push((x, y));
+            }
+        }
+        edges
+    }
+    /// Path through seen tiles to the closest reachable target, ordered for [`Player::moving_to`].
+    /// Targets on the player's own tile are skipped.
+    fn path_to_nearest(
+        &self,
+        mut targets: Vec<(usize, usize)>,
+        dungeon: &Dungeon,
+    ) -> Option<Vec<(usize, usize)>> {
+        let here = (self.x, self.y);
+        targets.retain(|f| *f != here);
+        let mut path = dungeon.pathfind_known(here, &targets, &self.tile_status)?;
+        // the path starts on the player's own tile
+        path.remove(0);
+        path.reverse();
+        Some(path)
+    }
+    /// Known tiles of the given landmark on this floor
+    fn landmark_positions(&self, landmark: Landmark, dungeon: &Dungeon) -> Vec<(usize, usize)> {
+        if let Landmark::Item = landmark {
+            return dungeon
+                .items
+                .iter()
+                .filter(|(x, y, _)| !self.tile_status[x + y * TILES_HORIZONTAL].is_unknown())
+                .map(|(x, y, _)| (*x, *y))
+                .collect();
+        }
+        dungeon
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(i, tile)| {
+                !self.tile_status[*i].is_unknown()
+                    && match landmark {
+                        Landmark::Exit => matches!(tile, Tile::Door),
+                        Landmark::Chest => matches!(tile, Tile::Chest(..)),
+                        Landmark::Ore => matches!(tile, Tile::Ore(..)),
+                        Landmark::Item => false,
+                    }
+            })
+            .map(|(i, _)| (i % TILES_HORIZONTAL, i / TILES_HORIZONTAL))
+            .collect()
+    }
+    /// Starts walking to the nearest known landmark of the given kind
+    fn travel_to(&mut self, landmark: Landmark, dungeon: &mut Dungeon) {
+        let targets = self.landmark_positions(landmark, dungeon);
+        if let Some(path) = self.path_to_nearest(targets, dungeon) {
+            self.moving_to = path;
+            self.travelling = true;
+        } else {
+            dungeon.screen_particles.push(Box::new(TextParticle {
+                text: format!("No known {}", landmark.name()),
+                origin: self.draw_pos - vec2(0.0, 4.0),
+                color: WHITE,
+            }));
+        }
+    }
+    /// Keeps auto-explore going by queueing up the path to the nearest unexplored area
+    /// (or item, with auto pickup). Stops once something needs the player's attention,
+    /// or once there's nothing left to explore.
+    fn explore(&mut self, dungeon: &mut Dungeon) {
+        let Some(seen) = self.exploring else {
+            return;
+        };
+        if self.auto_pickup {
+            self.pick_up_item(dungeon);
+        }
+        let found = self.count_points_of_interest(dungeon);
+        let stop_reason = if self.was_damaged {
+            Some("")
+        } else if !self.get_visible_enemies(dungeon).is_empty() {
+            Some("Enemy spotted")
+        } else if found > seen {
+            Some("Found something")
+        } else {
+            None
+        };
+        self.exploring = Some(found);
+
+        let mut path = None;
+        if stop_reason.is_none() {
+            let items: Vec<(usize, usize)> = if self.auto_pickup {
+                dungeon
+                    .items
+                    .iter()
+                    .filter(|(x, y, item)| {
+                        !self.tile_status[x + y * TILES_HORIZONTAL].is_unknown()
+                            && (self.get_stack_slot(item).is_some()
+                                || self.get_free_slot().is_some())
+                    })
+                    .map(|(x, y, _)| (*x, *y))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            path = self
+                .path_to_nearest(items, dungeon)
+                .or_else(|| self.path_to_nearest(self.unexplored_edges(dungeon), dungeon));
+        }
+        match (stop_reason, path) {
+            (None, Some(path)) => self.moving_to = path,
+            (reason, _) => {
+                self.exploring = None;
+                self.moving_to.clear();
+                let text = reason.unwrap_or("Explored");
+                if !text.is_empty() {
+                    dungeon.screen_particles.push(Box::new(TextParticle {
+                        text: String::from(text),
+                        origin: self.draw_pos - vec2(0.0, 4.0),
+                        color: WHITE,
+                    }));
+                }
+            }
+        }
+    }
+    /// Learned abilities followed by those granted by equipped items, in hotkey order
+    pub fn get_abilities(&self) -> Vec<&'static Ability> {
+        let mut abilities = self.learned_abilities.clone();
+        for item in self.inventory[..2].iter().flatten() {
+            for (granted_by, ability) in ITEM_ABILITIES {
+                if *granted_by == item.item && !abilities.contains(ability) {
+                    abilities.push(ability);
+                }
+            }
+        }
+        abilities.truncate(ABILITY_ACTIONS.len());
+        abilities
+    }
+    pub fn tick_cooldowns(&mut self) {
+        for cooldown in self.cooldowns.values_mut() {
+            *cooldown -= 1;
+        }
+        self.cooldowns.retain(|_, v| *v > 0);
+    }
+    /// Uses an ability in the given direction, putting it on cooldown.
+    ///
+    /// Returns `None` without using a turn if the ability has nothing to act on.
+    fn use_ability(
+        &mut self,
+        ability: &'static Ability,
+        dir: (isize, isize),
+        dungeon: &mut Dungeon,
+    ) -> Option<PlayerAction> {
+        self.aiming_ability = None;
+        let is_free = |pos: (usize, usize)| {
+            pos.0 < TILES_HORIZONTAL
+                && pos.1 < TILES_VERTICAL
+                && dungeon.tiles[pos.0 + pos.1 * TILES_HORIZONTAL].is_walkable()
+                && !dungeon.enemies.iter().any(|f| (f.x, f.y) == pos)
+        };
+        let adjacent = (
+            self.x.wrapping_add_signed(dir.0),
+            self.y.wrapping_add_signed(dir.1),
+        );
+        let (action, fail_text) = match ability.effect {
+            AbilityEffect::Dash { distance } => {
+                let mut pos = (self.x, self.y);
+                for _ in 0..distance {
+                    let next = (
+                        pos.0.wrapping_add_signed(dir.0),
+                        pos.1.wrapping_add_signed(dir.1),
+                    );
+                    if !is_free(next) {
+                        break;
+                    }
+                    pos = next;
+                }
+                let action = (pos != (self.x, self.y)).then(|| {
+                    let origin = self.draw_pos;
+                    (self.x, self.y) = pos;
+                    self.get_visible_tiles(dungeon);
+                    PlayerAction::Dash(origin)
+                });
+                (action, "Can't dash there")
+            }
+            AbilityEffect::ShieldBash { damage, knockback } => {
+                let hit = dungeon
+                    .enemies
+                    .iter_mut()
+                    .find(|f| (f.x, f.y) == adjacent)
+                    .map(|enemy| enemy.damage(damage));
+                // a dodged bash doesn't push the enemy
+                if hit == Some(true) {
+                    dungeon.knockback(adjacent, dir, knockback, (self.x, self.y));
+                }
+                let action = hit.map(|_| PlayerAction::Attack(vec2(dir.0 as f32, dir.1 as f32)));
+                (action, "Nothing to bash")
+            }
+            AbilityEffect::Whirlwind => {
+                let weapon = self.get_weapon();
+                let mut any_hit = false;
+                for enemy in dungeon.enemies.iter_mut() {
+                    if enemy.x.abs_diff(self.x) <= 1 && enemy.y.abs_diff(self.y) <= 1 {
+                        any_hit |= enemy.damage(weapon.base_damage);
+                        dungeon.particles.push(Box::new(TileFlashParticle {
+                            x: enemy.x,
+                            y: enemy.y,
+                            color: WHITE,
+                        }));
+                    }
+                }
+                if any_hit {
+                    self.wear_item(0, dungeon);
+                }
+                let action = dungeon
+                    .enemies
+                    .iter()
+                    .any(|f| f.x.abs_diff(self.x) <= 1 && f.y.abs_diff(self.y) <= 1)
+                    .then_some(PlayerAction::Wait);
+                (action, "No enemies nearby")
+            }
+            AbilityEffect::Heal(amount) => {
+                self.heal(amount);
+                dungeon.screen_particles.push(Box::new(TextParticle {
+                    text: format!("+{amount}"),
+                    origin: self.draw_pos - vec2(0.0, 4.0),
+                    color: GREEN,
+                }));
+                (Some(PlayerAction::Wait), "")
+            }
+        };
+        if action.is_some() {
+            self.cooldowns.insert(ability.name, ability.cooldown);
+        } else {
+            dungeon.screen_particles.push(Box::new(TextParticle {
+                text: String::from(fail_text),
+                origin: self.draw_pos - vec2(0.0, 4.0),
+                color: WHITE,
+            }));
+        }
+        action
+    }
+    /// Puts item in a free inventory slot, or drops it on the ground if the inventory is full.
+    pub fn give_item(&mut self, item: ItemInstance, dungeon: &mut Dungeon) {
+        let name = self.identification.get_name(&item);
+        if let Some(slot) = self.get_stack_slot(&item).or(self.get_free_slot()) {
+            events::emit(GameEvent::ItemPickedUp {
+                name,
+                dropped: false,
+            });
+            self.put_item(slot, item);
+        } else {
+            events::emit(GameEvent::ItemPickedUp {
+                name,
+                dropped: true,
+            });
+            dungeon.items.push((self.x, self.y, item));
+        }
+    }
+    /// Puts item in slot, adding it to the stack already there if there is one
+    fn put_item(&mut self, slot: usize, item: ItemInstance) {
+        if let Some(stack) = &mut self.inventory[slot] {
+            stack.count += item.count;
+        } else {
+            self.inventory[slot] = Some(item);
+        }
+    }
     pub fn get_free_slot(&self) -> Option<usize> {
-        for (i, slot) in self.inventory.iter().enumerate().skip(2) {
+        for (i, slot) in self.inventory.iter().enumerate().take(QUIVER_SLOT).skip(2) {
             if slot.is_none() {
                 return Some(i);
             }
         }
         None
     }
+    /// Finds a slot with a stack the item can be added to, preferring the quiver
+    pub fn get_stack_slot(&self, item: &ItemInstance) -> Option<usize> {
+        std::iter::once(QUIVER_SLOT)
+            .chain(2..QUIVER_SLOT)
+            .find(|i| {
+                self.inventory[*i]
+                    .as_ref()
+                    .is_some_and(|f| f.stacks_with(item))
+            })
+    }
+    /// The kind of arrow in the quiver
+    fn quiver_ammo(&self) -> Option<&'static Ammo> {
+        match self.inventory[QUIVER_SLOT].as_ref()?.item {
+            Item::Ammo(ammo) => Some(ammo),
+            _ => None,
+        }
+    }
+    /// Removes a single arrow from the quiver
+    fn take_ammo(&mut self) {
+        if let Some(quiver) = &mut self.inventory[QUIVER_SLOT] {
+            quiver.count -= 1;
+            if quiver.count == 0 {
+                self.inventory[QUIVER_SLOT] = None;
+            }
+        }
+    }
 }
 
 pub enum MovementType {
@@ -441,6 +1644,7 @@ pub enum MovementType {
 }
 
 pub struct EnemyType {
+    pub name: &'static str,
     pub block_chance: f32,
     pub death_drops: Option<&'static LootTable>,
     pub sprite_x: f32,
@@ -449,9 +1653,12 @@ pub struct EnemyType {
     pub movement_type: MovementType,
     pub weapon: &'static Weapon,
     pub show_held_item: bool,
+    /// Experience the player gets for killing this enemy
+    pub xp: u32,
 }
 
 pub static ZOMBIE: LazyLock<EnemyType> = LazyLock::new(|| EnemyType {
+    name: "zombie",
     block_chance: 0.1,
     death_drops: Some(&ZOMBIE_DROPS),
     sprite_x: 0.0,
@@ -460,8 +1667,10 @@ pub static ZOMBIE: LazyLock<EnemyType> = LazyLock::new(|| EnemyType {
     movement_type: MovementType::ChaseWhenVisible,
     weapon: &MELEE,
     show_held_item: false,
+    xp: 3,
 });
 pub static SKELETON: LazyLock<EnemyType> = LazyLock::new(|| EnemyType {
+    name: "skeleton",
     block_chance: 0.1,
     death_drops: Some(&SKELETON_DROPS),
     sprite_x: 0.0,
@@ -470,8 +1679,10 @@ pub static SKELETON: LazyLock<EnemyType> = LazyLock::new(|| EnemyType {
     movement_type: MovementType::AlwaysChase,
     weapon: &SHORTBOW,
     show_held_item: false,
+    xp: 4,
 });
 pub static SPIDER: LazyLock<EnemyType> = LazyLock::new(|| EnemyType {
+    name: "spider",
     block_chance: 0.5,
     death_drops: Some(&SPIDER_DROPS),
     sprite_x: 0.0,
@@ -480,8 +1691,10 @@ pub static SPIDER: LazyLock<EnemyType> = LazyLock::new(|| EnemyType {
     movement_type: MovementType::AlwaysChase,
     weapon: &MELEE,
     show_held_item: false,
+    xp: 3,
 });
 pub static BAT: EnemyType = EnemyType {
+    name: "bat",
     block_chance: 0.8,
     death_drops: None,
     sprite_x: 0.0,
@@ -490,8 +1703,10 @@ pub static BAT: EnemyType = EnemyType {
     movement_type: MovementType::ChaseWhenVisible,
     weapon: &MELEE,
     show_held_item: false,
+    xp: 2,
 };
 pub static SLIME: LazyLock<EnemyType> = LazyLock::new(|| EnemyType {
+    name: "slime",
     block_chance: 0.0,
     death_drops: Some(&SLIME_DROPS),
     sprite_x: 0.0,
@@ -500,18 +1715,22 @@ pub static SLIME: LazyLock<EnemyType> = LazyLock::new(|| EnemyType {
     movement_type: MovementType::ChaseWhenVisible,
     weapon: &MELEE,
     show_held_item: false,
+    xp: 4,
 });
-pub static WIZARD: EnemyType = EnemyType {
+pub static WIZARD: LazyLock<EnemyType> = LazyLock::new(|| EnemyType {
+    name: "wizard",
     block_chance: 0.1,
-    death_drops: None,
+    death_drops: Some(&WIZARD_DROPS),
     sprite_x: 0.0,
     sprite_y: 8.0,
     max_health: 10.0,
     movement_type: MovementType::AlwaysChase,
     weapon: &SPELLBOOK,
     show_held_item: false,
-};
+    xp: 6,
+});
 pub static SUPER_WIZARD: EnemyType = EnemyType {
+    name: "archwizard",
     block_chance: 0.2,
     death_drops: None,
     sprite_x: 1.0,
@@ -520,8 +1739,10 @@ pub static SUPER_WIZARD: EnemyType = EnemyType {
     movement_type: MovementType::AlwaysChase,
     weapon: &SUPER_SPELLBOOK,
     show_held_item: false,
+    xp: 30,
 };
 pub static LAVA_DOG: EnemyType = EnemyType {
+    name: "lava dog",
     block_chance: 0.6,
     death_drops: None,
     sprite_x: 0.0,
@@ -530,12 +1751,15 @@ pub static LAVA_DOG: EnemyType = EnemyType {
     movement_type: MovementType::AlwaysChase,
     weapon: &MELEE,
     show_held_item: false,
+    xp: 5,
 };
 
 pub enum EnemyAction {
     MoveTo((usize, usize)),
     Attack(Vec2),
     Wait,
+    /// Knocked back during the player's turn, from the stored draw position
+    Pushed(Vec2),
 }
 
 pub struct Enemy {
@@ -571,25 +1795,27 @@ impl Enemy {
             status_effects: HashMap::new(),
         }
     }
-    pub fn damage_throwing(
-        &mut self,
-        amt: f32,
-        status: Option<StatusEffect>,
-        particles: &mut Vec<Box<dyn ScreenParticle>>,
-    ) -> bool {
+    pub fn damage_throwing(&mut self, amt: f32, status: Option<StatusEffect>) -> bool {
         let mut hits = true;
         if amt > 0.0 {
-            hits = self.damage(amt, particles);
+            hits = self.damage(amt);
         }
         if hits && let Some(status) = status {
-            if let Some(e) = self.status_effects.get_mut(&status) {
-                *e += 3;
-            } else {
-                self.status_effects.insert(status, 3);
-            }
+            self.apply_status(status);
         }
         hits
     }
+    pub fn apply_status(&mut self, status: StatusEffect) {
+        events::emit(GameEvent::StatusApplied {
+            target: Target::Enemy(self.ty.name),
+            status,
+        });
+        if let Some(e) = self.status_effects.get_mut(&status) {
+            *e += status.duration();
+        } else {
+            self.status_effects.insert(status, status.duration());
+        }
+    }
     pub fn reset_draw_pos(&mut self) {
         self.draw_pos = vec2((self.x * 8) as f32, (self.y * 8) as f32);
     }
@@ -598,12 +1824,17 @@ impl Enemy {
         self.just_awoke = true;
     }
     pub fn update(&mut self, delta_time: f32, state: &GameState) {
-        if let GameState::EnemyAction(time) = state
+        if let GameState::PlayerAction(time) = state
+            && let Some(EnemyAction::Pushed(origin)) = self.current_action
+        {
+            let target = vec2((self.x * 8) as f32, (self.y * 8) as f32);
+            self.draw_pos = origin.lerp(target, (1.0 - *time / ACTION_TIME).clamp(0.0, 1.0));
+        } else if let GameState::EnemyAction(time) = state
             && let Some(current_action) = &self.current_action
         {
             let animation_time = ACTION_TIME - *time;
             match current_action {
-                EnemyAction::Wait => {}
+                EnemyAction::Wait | EnemyAction::Pushed(_) => {}
                 EnemyAction::MoveTo(pos) => {
                     let speed = delta_time * 8.0 / ACTION_TIME;
                     let target = vec2(pos.0 as f32 * 8.0, pos.1 as f32 * 8.0);
@@ -623,27 +1854,42 @@ impl Enemy {
             self.reset_draw_pos();
         }
     }
-    pub fn damage(&mut self, amt: f32, particles: &mut Vec<Box<dyn ScreenParticle>>) -> bool {
+    /// Damages the enemy, ignoring its block chance
+    pub fn damage_undodgeable(&mut self, amt: f32, source: DamageSource) {
+        self.awaken();
+        self.take_damage(amt, source);
+    }
+    pub fn damage(&mut self, amt: f32) -> bool {
         let rng = rand::gen_range(0.0, 1.0);
         if !self.awake || self.ty.block_chance < rng {
-            self.was_damaged = true;
-            self.health -= amt;
-            particles.push(Box::new(TextParticle {
-                text: format!("-{amt}"),
-                origin: self.draw_pos - vec2(0.0, 4.0),
-                color: GREEN,
-            }));
+            self.take_damage(amt, DamageSource::Player);
             true
         } else {
-            particles.push(Box::new(TextParticle {
-                text: String::from("Dodged!"),
-                origin: self.draw_pos - vec2(0.0, 4.0),
-                color: RED,
-            }));
+            events::emit(GameEvent::Dodged {
+                target: Target::Enemy(self.ty.name),
+                blocked: false,
+                pos: self.draw_pos - vec2(0.0, 4.0),
+            });
             false
         }
     }
-    pub fn act(&mut self, dungeon: &mut Dungeon, player: &mut Player) -> EnemyAction {
+    /// Lowers the enemy's health without waking it up
+    fn take_damage(&mut self, amt: f32, source: DamageSource) {
+        self.was_damaged = true;
+        self.health -= amt;
+        events::emit(GameEvent::DamageDealt {
+            target: Target::Enemy(self.ty.name),
+            source,
+            amount: amt,
+            pos: self.draw_pos - vec2(0.0, 4.0),
+        });
+    }
+    pub fn act(
+        &mut self,
+        dungeon: &mut Dungeon,
+        player: &mut Player,
+        enemy_positions: &[(usize, usize)],
+    ) -> EnemyAction {
         if self.just_awoke {
             self.just_awoke = false;
         }
@@ -654,46 +1900,49 @@ impl Enemy {
             player.x as f32 - self.x as f32,
             player.y as f32 - self.y as f32,
         );
-        for (k, v) in self.status_effects.iter_mut() {
-            if let StatusEffect::Poison = k {
-                self.health -= 2.0;
-                self.was_damaged = true;
-            }
-
+        let frozen = self.status_effects.contains_key(&StatusEffect::Frozen);
+        if self.status_effects.contains_key(&StatusEffect::Poison) {
+            self.take_damage(2.0, DamageSource::Poison);
+        }
+        for v in self.status_effects.values_mut() {
             *v -= 1;
         }
-        self.status_effects.retain(|_, v| *v > 0);
-        if self
-            .ty
-            .weapon
-            .attack_range
-            .contains(&((delta.length()) as usize))
-        {
-            let hits = if delta.length() as usize <= 1 {
-                true
-            } else {
-                let pos = vec2(player.x as f32, player.y as f32);
-                let self_pos = vec2(self.x as f32, self.y as f32);
-                let delta_normalized = (pos - self_pos).normalize();
-                let mut current = self_pos;
-                let max_step = 0.15;
-                let mut hit = false;
-                loop {
-                    current += delta_normalized * max_step;
-
-                    let (tx, ty) = ((current.x).round() as usize, (current.y).round() as usize);
-                    if !dungeon.tiles[tx + ty * TILES_HORIZONTAL].is_walkable() {
-                        break;
-                    }
-                    if (player.x, player.y) == (tx, ty) {
-                        hit = true;
-                        break;
-                    }
-                }
-                hit
-            };
+        let seen = matches!(
+            player.tile_status[self.x + self.y * TILES_HORIZONTAL],
+            TileStatus::Known
+        );
+        for (effect, _) in self.status_effects.extract_if(|_, v| *v == 0) {
+            if seen {
+                events::emit(GameEvent::StatusExpired {
+                    target: Target::Enemy(self.ty.name),
+                    status: effect,
+                });
+            }
+        }
+        if frozen {
+            return EnemyAction::Wait;
+        }
+        let player_pos = (player.x, player.y);
+        let weapon = self.ty.weapon;
+        if weapon.in_range((self.x, self.y), player_pos, dungeon.eight_way) {
+            // other enemies block the line of fire, so don't shoot through them
+            let hits = weapon.distance((self.x, self.y), player_pos, dungeon.eight_way) <= 1
+                || trace(
+                    &dungeon.tiles,
+                    |pos| pos == player_pos || enemy_positions.contains(&pos),
+                    (self.x, self.y),
+                    player_pos,
+                    &self.ty.weapon.projectile,
+                    false,
+                )
+                .first_hit()
+                    == Some(player_pos);
             if hits {
-                player.damage(self.ty.weapon.base_damage, dungeon);
+                player.damage(
+                    self.ty.weapon.base_damage,
+                    DamageSource::Enemy(self.ty.name),
+                    dungeon,
+                );
 
                 if let Some(particle) = self.ty.weapon.fires_particle {
                     dungeon.particles.push(Box::new(ProjectileParticle {
@@ -820,3 +2069,73 @@ impl Enemy {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{classes::CLASSES, dungeon::DUNGEON_FLOORS, entities::*};
+
+    #[test]
+    fn test_levelling() {
+        let mut player = Player::new(&CLASSES[0]);
+        assert!(!player.give_xp(XP_PER_LEVEL - 1));
+        // enough for two level ups at once
+        assert!(player.give_xp(1 + XP_PER_LEVEL * 2));
+        assert_eq!(
+            (player.level, player.xp, player.attribute_points),
+            (3, 0, 2)
+        );
+
+        let max_health = player.max_health();
+        player.raise_attribute(Attribute::Vitality);
+        player.raise_attribute(Attribute::Vitality);
+        player.raise_attribute(Attribute::Vitality);
+        assert_eq!(player.attribute_points, 0);
+        assert_eq!(player.max_health(), max_health + 2.0 * HP_PER_VITALITY);
+    }
+
+    #[test]
+    fn test_regeneration() {
+        let mut player = Player::new(&CLASSES[0]);
+        assert_eq!(player.regen_rate(), BASE_REGEN);
+        player.nutrition = HUNGRY_NUTRITION - 1;
+        assert_eq!(player.regen_rate(), BASE_REGEN / 2.0);
+        player.apply_status(StatusEffect::Poison);
+        assert_eq!(player.regen_rate(), 0.0);
+
+        player.nutrition = MAX_NUTRITION;
+        player.status_effects.clear();
+        player.apply_status(StatusEffect::Regeneration);
+        assert_eq!(player.regen_rate(), BASE_REGEN + REGEN_STATUS_RATE);
+    }
+
+    #[test]
+    fn test_inspect() {
+        let mut tiles = vec![Tile::Floor; TILES_HORIZONTAL * TILES_VERTICAL];
+        tiles[3] = Tile::Door;
+        let mut dungeon = Dungeon {
+            tiles,
+            player_spawn: (0, 0),
+            enemies: vec![Enemy::new(1, 0, &BAT)],
+            particles: Vec::new(),
+            screen_particles: Vec::new(),
+            items: Vec::new(),
+            dungeon_floor: &DUNGEON_FLOORS[0],
+            eight_way: false,
+        };
+        let mut player = Player::new(&CLASSES[0]);
+        assert!(player.inspect((1, 0), &dungeon).is_none());
+
+        player.tile_status[1] = TileStatus::Known;
+        player.tile_status[3] = TileStatus::Remembered;
+        let (title, lines) = player.inspect((1, 0), &dungeon).unwrap();
+        assert_eq!(title, "BAT");
+        assert!(lines.contains(&String::from("ASLEEP")));
+
+        // enemies on remembered tiles aren't seen, but the door is
+        dungeon.enemies[0].x = 3;
+        let (title, lines) = player.inspect((3, 0), &dungeon).unwrap();
+        assert_eq!(title, "DOOR");
+        assert_eq!(lines.last().unwrap(), "REMEMBERED");
+        assert!(player.inspect((1, 0), &dungeon).is_none());
+    }
+}
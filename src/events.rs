@@ -0,0 +1,144 @@
+use std::cell::RefCell;
+
+use macroquad::prelude::*;
+
+use crate::{entities::EnemyType, items::StatusEffect};
+
+/// Who an event happened to
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Target {
+    Player,
+    /// An enemy, by name
+    Enemy(&'static str),
+}
+
+/// What dealt damage
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DamageSource {
+    /// The player's attacks, throws, scrolls and abilities
+    Player,
+    /// An enemy's attack, by the enemy's name
+    Enemy(&'static str),
+    Poison,
+    Starvation,
+    /// Being knocked into a wall or another enemy
+    Slam,
+}
+impl DamageSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageSource::Player => "you",
+            DamageSource::Enemy(name) => name,
+            DamageSource::Poison => "poison",
+            DamageSource::Starvation => "starvation",
+            DamageSource::Slam => "slams",
+        }
+    }
+}
+
+/// Something that happened in the game. Game logic emits these with [`emit`], and everything
+/// reacting to them (particles, the message log, stats, loot) picks them up from [`drain`].
+#[derive(Clone)]
+pub enum GameEvent {
+    /// `pos` is the draw position of whoever was hit, for placing particles
+    DamageDealt {
+        target: Target,
+        source: DamageSource,
+        amount: f32,
+        pos: Vec2,
+    },
+    /// An attack that missed. `blocked` is set when the player's armor stopped it.
+    Dodged {
+        target: Target,
+        blocked: bool,
+        pos: Vec2,
+    },
+    EnemyKilled {
+        enemy: &'static EnemyType,
+        pos: (usize, usize),
+    },
+    /// An item put in the inventory, or dropped on the floor when there was no room for it
+    ItemPickedUp {
+        name: String,
+        dropped: bool,
+    },
+    ItemCrafted {
+        name: String,
+        ingredients: (String, String),
+    },
+    ItemSalvaged {
+        name: String,
+    },
+    ItemThrown {
+        name: String,
+    },
+    ItemBroke {
+        name: String,
+        pos: Vec2,
+    },
+    StatusApplied {
+        target: Target,
+        status: StatusEffect,
+    },
+    StatusExpired {
+        target: Target,
+        status: StatusEffect,
+    },
+    LevelUp {
+        level: u32,
+        pos: Vec2,
+    },
+    /// `floor` counts from 0
+    FloorEntered {
+        floor: usize,
+    },
+    PlayerDied {
+        floor: usize,
+    },
+    /// The last floor was cleared
+    Won,
+}
+
+thread_local! {
+    static QUEUE: RefCell<Vec<GameEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn emit(event: GameEvent) {
+    QUEUE.with_borrow_mut(|f| f.push(event));
+}
+/// Takes every event emitted since the last call, oldest first
+pub fn drain() -> Vec<GameEvent> {
+    QUEUE.with_borrow_mut(std::mem::take)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{entities::*, events::*};
+
+    #[test]
+    fn test_events() {
+        drain();
+        let mut enemy = Enemy::new(1, 1, &BAT);
+        enemy.damage_undodgeable(2.0, DamageSource::Player);
+        enemy.apply_status(StatusEffect::Poison);
+        let events = drain();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[0],
+            GameEvent::DamageDealt {
+                target: Target::Enemy("bat"),
+                source: DamageSource::Player,
+                amount: 2.0,
+                pos: Vec2 { x: 8.0, y: 4.0 },
+            }
+        ));
+        assert!(matches!(
+            events[1],
+            GameEvent::StatusApplied {
+                target: Target::Enemy("bat"),
+                status: StatusEffect::Poison,
+            }
+        ));
+        assert!(drain().is_empty());
+    }
+}
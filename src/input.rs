@@ -0,0 +1,729 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{LazyLock, Mutex, MutexGuard},
+};
+
+use macroquad::prelude::*;
+
+/// File the bindings are loaded from and saved to, next to the executable's working directory
+const CONFIG_PATH: &str = "controls.cfg";
+
+/// Which part of the game an action is used in. Actions can only conflict within the same context.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Context {
+    Game,
+    /// The inventory's context menu
+    Menu,
+    /// Used both in game and while the context menu is open
+    Both,
+}
+impl Context {
+    fn overlaps(self, other: Context) -> bool {
+        self == other || self == Context::Both || other == Context::Both
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    /// Diagonal steps, only used with eight-way movement
+    MoveUpLeft,
+    MoveUpRight,
+    MoveDownLeft,
+    MoveDownRight,
+    /// Held while moving into an enemy to shove it
+    Shove,
+    Wait,
+    Rest,
+    Interact,
+    Inventory,
+    /// Closes the inventory, or the targeting cursor
+    Cancel,
+    Explore,
+    AutoPickup,
+    /// Toggles diagonal movement
+    EightWay,
+    TravelExit,
+    TravelChest,
+    TravelOre,
+    TravelItem,
+    /// Opens the targeting cursor and cycles through targets
+    Target,
+    /// Confirms the targeting cursor, or attacks the nearest enemy when there's no cursor
+    Confirm,
+    /// Cycles through zoom levels
+    Zoom,
+    Ability1,
+    Ability2,
+    Ability3,
+    Ability4,
+    Ability5,
+    /// Held to pan the camera with the mouse
+    Pan,
+    Controls,
+    /// Opens the full message log
+    MessageLog,
+    /// Opens the table of past runs
+    HighScores,
+    /// Moves the context menu highlight
+    MenuUp,
+    MenuDown,
+    MenuMove,
+    MenuConsume,
+    MenuRead,
+    MenuEquip,
+    MenuCombine,
+    MenuThrow,
+    MenuSalvage,
+    MenuDrop,
+}
+/// Every action with its name in the config file, in the order they're listed
+pub const ACTIONS: &[(Action, &str)] = &[
+    (Action::MoveUp, "move_up"),
+    (Action::MoveDown, "move_down"),
+    (Action::MoveLeft, "move_left"),
+    (Action::MoveRight, "move_right"),
+    (Action::MoveUpLeft, "move_up_left"),
+    (Action::MoveUpRight, "move_up_right"),
+    (Action::MoveDownLeft, "move_down_left"),
+    (Action::MoveDownRight, "move_down_right"),
+    (Action::Shove, "shove"),
+    (Action::Wait, "wait"),
+    (Action::Rest, "rest"),
+    (Action::Interact, "interact"),
+    (Action::Inventory, "inventory"),
+    (Action::Cancel, "cancel"),
+    (Action::Explore, "explore"),
+    (Action::AutoPickup, "auto_pickup"),
+    (Action::EightWay, "eight_way"),
+    (Action::TravelExit, "travel_exit"),
+    (Action::TravelChest, "travel_chest"),
+    (Action::TravelOre, "travel_ore"),
+    (Action::TravelItem, "travel_item"),
+    (Action::Target, "target"),
+    (Action::Confirm, "confirm"),
+    (Action::Zoom, "zoom"),
+    (Action::Ability1, "ability_1"),
+    (Action::Ability2, "ability_2"),
+    (Action::Ability3, "ability_3"),
+    (Action::Ability4, "ability_4"),
+    (Action::Ability5, "ability_5"),
+    (Action::Pan, "pan"),
+    (Action::Controls, "controls"),
+    (Action::MessageLog, "message_log"),
+    (Action::HighScores, "high_scores"),
+    (Action::MenuUp, "menu_up"),
+    (Action::MenuDown, "menu_down"),
+    (Action::MenuMove, "menu_move"),
+    (Action::MenuConsume, "menu_consume"),
+    (Action::MenuRead, "menu_read"),
+    (Action::MenuEquip, "menu_equip"),
+    (Action::MenuCombine, "menu_combine"),
+    (Action::MenuThrow, "menu_throw"),
+    (Action::MenuSalvage, "menu_salvage"),
+    (Action::MenuDrop, "menu_drop"),
+];
+/// Abilities in hotkey order
+pub const ABILITY_ACTIONS: &[Action] = &[
+    Action::Ability1,
+    Action::Ability2,
+    Action::Ability3,
+    Action::Ability4,
+    Action::Ability5,
+];
+impl Action {
+    pub fn name(self) -> &'static str {
+        ACTIONS.iter().find(|(f, _)| *f == self).unwrap().1
+    }
+    pub fn context(self) -> Context {
+        match self {
+            Action::MenuUp
+            | Action::MenuDown
+            | Action::MenuMove
+            | Action::MenuConsume
+            | Action::MenuRead
+            | Action::MenuEquip
+            | Action::MenuCombine
+            | Action::MenuThrow
+            | Action::MenuSalvage
+            | Action::MenuDrop => Context::Menu,
+            Action::Confirm
+            | Action::Cancel
+            | Action::Inventory
+            | Action::Controls
+            | Action::MessageLog
+            | Action::HighScores => Context::Both,
+            _ => Context::Game,
+        }
+    }
+}
+
+/// Keys which can be bound, looked up by their names in the config file
+const BINDABLE_KEYS: &[KeyCode] = &[
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Kp0,
+    KeyCode::Kp1,
+    KeyCode::Kp2,
+    KeyCode::Kp3,
+    KeyCode::Kp4,
+    KeyCode::Kp5,
+    KeyCode::Kp6,
+    KeyCode::Kp7,
+    KeyCode::Kp8,
+    KeyCode::Kp9,
+    KeyCode::KpEnter,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Tab,
+    KeyCode::Escape,
+    KeyCode::Backspace,
+    KeyCode::LeftShift,
+    KeyCode::RightShift,
+    KeyCode::LeftControl,
+    KeyCode::RightControl,
+    KeyCode::LeftAlt,
+    KeyCode::RightAlt,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+];
+const BINDABLE_BUTTONS: &[MouseButton] = &[MouseButton::Middle, MouseButton::Right];
+
+/// Gamepad buttons, named by their position like gilrs does. The left stick also presses the d-pad.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftTrigger,
+    RightTrigger,
+    LeftTrigger2,
+    RightTrigger2,
+    Select,
+    Start,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+const PAD_BUTTONS: &[PadButton] = &[
+    PadButton::South,
+    PadButton::East,
+    PadButton::North,
+    PadButton::West,
+    PadButton::LeftTrigger,
+    PadButton::RightTrigger,
+    PadButton::LeftTrigger2,
+    PadButton::RightTrigger2,
+    PadButton::Select,
+    PadButton::Start,
+    PadButton::LeftThumb,
+    PadButton::RightThumb,
+    PadButton::DPadUp,
+    PadButton::DPadDown,
+    PadButton::DPadLeft,
+    PadButton::DPadRight,
+];
+impl PadButton {
+    /// Name printed on an xbox style controller
+    pub fn label(self) -> &'static str {
+        match self {
+            PadButton::South => "A",
+            PadButton::East => "B",
+            PadButton::North => "Y",
+            PadButton::West => "X",
+            PadButton::LeftTrigger => "LB",
+            PadButton::RightTrigger => "RB",
+            PadButton::LeftTrigger2 => "LT",
+            PadButton::RightTrigger2 => "RT",
+            PadButton::Select => "Back",
+            PadButton::Start => "Start",
+            PadButton::LeftThumb => "LS",
+            PadButton::RightThumb => "RS",
+            PadButton::DPadUp => "Up",
+            PadButton::DPadDown => "Down",
+            PadButton::DPadLeft => "Left",
+            PadButton::DPadRight => "Right",
+        }
+    }
+    #[cfg(feature = "gamepad")]
+    fn to_gilrs(self) -> gilrs::Button {
+        match self {
+            PadButton::South => gilrs::Button::South,
+            PadButton::East => gilrs::Button::East,
+            PadButton::North => gilrs::Button::North,
+            PadButton::West => gilrs::Button::West,
+            PadButton::LeftTrigger => gilrs::Button::LeftTrigger,
+            PadButton::RightTrigger => gilrs::Button::RightTrigger,
+            PadButton::LeftTrigger2 => gilrs::Button::LeftTrigger2,
+            PadButton::RightTrigger2 => gilrs::Button::RightTrigger2,
+            PadButton::Select => gilrs::Button::Select,
+            PadButton::Start => gilrs::Button::Start,
+            PadButton::LeftThumb => gilrs::Button::LeftThumb,
+            PadButton::RightThumb => gilrs::Button::RightThumb,
+            PadButton::DPadUp => gilrs::Button::DPadUp,
+            PadButton::DPadDown => gilrs::Button::DPadDown,
+            PadButton::DPadLeft => gilrs::Button::DPadLeft,
+            PadButton::DPadRight => gilrs::Button::DPadRight,
+        }
+    }
+}
+
+/// How far the left stick has to be pushed to count as a d-pad press
+#[cfg(feature = "gamepad")]
+const STICK_DEADZONE: f32 = 0.5;
+
+/// Buttons held on any connected gamepad, this frame and the last.
+/// Without the `gamepad` feature nothing is ever held.
+struct Pad {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    down: Vec<PadButton>,
+    last_down: Vec<PadButton>,
+    /// Whether the pad was used more recently than the keyboard and mouse, so the hud shows pad buttons
+    active: bool,
+}
+impl Pad {
+    fn new() -> Self {
+        Self {
+            #[cfg(feature = "gamepad")]
+            gilrs: gilrs::Gilrs::new().ok(),
+            down: Vec::new(),
+            last_down: Vec::new(),
+            active: false,
+        }
+    }
+    fn update(&mut self) {
+        self.last_down = std::mem::take(&mut self.down);
+        #[cfg(feature = "gamepad")]
+        if let Some(gilrs) = &mut self.gilrs {
+            while gilrs.next_event().is_some() {}
+            for (_, gamepad) in gilrs.gamepads() {
+                for button in PAD_BUTTONS {
+                    if gamepad.is_pressed(button.to_gilrs()) {
+                        self.down.push(*button);
+                    }
+                }
+                let x = gamepad.value(gilrs::Axis::LeftStickX);
+                let y = gamepad.value(gilrs::Axis::LeftStickY);
+                for (held, button) in [
+                    (x < -STICK_DEADZONE, PadButton::DPadLeft),
+                    (x > STICK_DEADZONE, PadButton::DPadRight),
+                    (y > STICK_DEADZONE, PadButton::DPadUp),
+                    (y < -STICK_DEADZONE, PadButton::DPadDown),
+                ] {
+                    if held {
+                        self.down.push(button);
+                    }
+                }
+            }
+        }
+        if self.get_pressed().is_some() {
+            self.active = true;
+        } else if get_last_key_pressed().is_some() || is_mouse_button_pressed(MouseButton::Left) {
+            self.active = false;
+        }
+    }
+    fn is_pressed(&self, button: PadButton) -> bool {
+        self.down.contains(&button) && !self.last_down.contains(&button)
+    }
+    fn get_pressed(&self) -> Option<PadButton> {
+        PAD_BUTTONS.iter().copied().find(|f| self.is_pressed(*f))
+    }
+}
+
+thread_local! {
+    static PAD: RefCell<Pad> = RefCell::new(Pad::new());
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Pad(PadButton),
+}
+impl Binding {
+    /// Name used in the config file
+    pub fn name(self) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::Mouse(button) => format!("Mouse{button:?}"),
+            Binding::Pad(button) => format!("Pad{button:?}"),
+        }
+    }
+    /// Shorter name shown in the hud, e.g. "1" for "Key1" and "LShift" for "LeftShift"
+    pub fn label(self) -> String {
+        if let Binding::Pad(button) = self {
+            return button.label().to_string();
+        }
+        let name = self.name();
+        if let Some(digit) = name.strip_prefix("Key") {
+            return digit.to_string();
+        }
+        for (side, short) in [("Left", "L"), ("Right", "R")] {
+            if let Some(key) = name.strip_prefix(side)
+                && !key.is_empty()
+            {
+                return format!("{short}{key}");
+            }
+        }
+        name
+    }
+    pub fn parse(name: &str) -> Option<Binding> {
+        if let Some(button) = name.strip_prefix("Mouse") {
+            return BINDABLE_BUTTONS
+                .iter()
+                .find(|f| format!("{f:?}").eq_ignore_ascii_case(button))
+                .map(|f| Binding::Mouse(*f));
+        }
+        if let Some(button) = name.strip_prefix("Pad") {
+            return PAD_BUTTONS
+                .iter()
+                .find(|f| format!("{f:?}").eq_ignore_ascii_case(button))
+                .map(|f| Binding::Pad(*f));
+        }
+        BINDABLE_KEYS
+            .iter()
+            .find(|f| format!("{f:?}").eq_ignore_ascii_case(name))
+            .map(|f| Binding::Key(*f))
+    }
+    fn is_pressed(self) -> bool {
+        match self {
+            Binding::Key(key) => is_key_pressed(key),
+            Binding::Mouse(button) => is_mouse_button_pressed(button),
+            Binding::Pad(button) => PAD.with_borrow(|f| f.is_pressed(button)),
+        }
+    }
+    fn is_down(self) -> bool {
+        match self {
+            Binding::Key(key) => is_key_down(key),
+            Binding::Mouse(button) => is_mouse_button_down(button),
+            Binding::Pad(button) => PAD.with_borrow(|f| f.down.contains(&button)),
+        }
+    }
+    /// The bindable key or button pressed this frame, if any
+    pub fn get_pressed() -> Option<Binding> {
+        if let Some(key) = get_last_key_pressed()
+            && BINDABLE_KEYS.contains(&key)
+        {
+            return Some(Binding::Key(key));
+        }
+        if let Some(button) = PAD.with_borrow(|f| f.get_pressed()) {
+            return Some(Binding::Pad(button));
+        }
+        BINDABLE_BUTTONS
+            .iter()
+            .find(|f| is_mouse_button_pressed(**f))
+            .map(|f| Binding::Mouse(*f))
+    }
+}
+
+/// Keys and buttons bound to each action. An action can have any amount of bindings.
+pub struct Bindings(HashMap<Action, Vec<Binding>>);
+impl Default for Bindings {
+    fn default() -> Self {
+        use KeyCode::*;
+        let keys = |keys: &[KeyCode]| keys.iter().map(|f| Binding::Key(*f)).collect();
+        let mut bindings = HashMap::new();
+        for (action, _) in ACTIONS {
+            let mut bound: Vec<Binding> = match action {
+                Action::MoveUp => keys(&[W, Up, Kp8]),
+                Action::MoveDown => keys(&[S, Down, Kp2]),
+                Action::MoveLeft => keys(&[A, Left, Kp4]),
+                Action::MoveRight => keys(&[D, Right, Kp6]),
+                Action::MoveUpLeft => keys(&[Kp7, Y]),
+                Action::MoveUpRight => keys(&[Kp9, U]),
+                Action::MoveDownLeft => keys(&[Kp1, B]),
+                Action::MoveDownRight => keys(&[Kp3, N]),
+                Action::Shove => keys(&[LeftShift, RightShift]),
+                Action::Wait => keys(&[H, Kp5]),
+                Action::Rest => keys(&[R]),
+                Action::Interact => keys(&[E]),
+                Action::Inventory => keys(&[F]),
+                Action::Cancel => keys(&[Escape]),
+                Action::Explore => keys(&[X]),
+                Action::AutoPickup => keys(&[P]),
+                Action::EightWay => keys(&[V]),
+                Action::TravelExit => keys(&[G]),
+                Action::TravelChest => keys(&[C]),
+                Action::TravelOre => keys(&[O]),
+                Action::TravelItem => keys(&[I]),
+                Action::Target => keys(&[Tab]),
+                Action::Confirm => keys(&[Enter]),
+                Action::Zoom => keys(&[Z]),
+                Action::Ability1 => keys(&[Key1]),
+                Action::Ability2 => keys(&[Key2]),
+                Action::Ability3 => keys(&[Key3]),
+                Action::Ability4 => keys(&[Key4]),
+                Action::Ability5 => keys(&[Key5]),
+                Action::Pan => vec![Binding::Mouse(MouseButton::Middle)],
+                Action::Controls => keys(&[F1]),
+                Action::MessageLog => keys(&[L]),
+                Action::HighScores => keys(&[F2]),
+                Action::MenuUp => keys(&[Up, Kp8]),
+                Action::MenuDown => keys(&[Down, Kp2]),
+                Action::MenuMove => keys(&[M]),
+                Action::MenuConsume => keys(&[Q]),
+                Action::MenuRead => keys(&[R]),
+                Action::MenuEquip => keys(&[E]),
+                Action::MenuCombine => keys(&[C]),
+                Action::MenuThrow => keys(&[T]),
+                Action::MenuSalvage => keys(&[S]),
+                Action::MenuDrop => keys(&[D]),
+            };
+            let pad = match action {
+                Action::MoveUp => Some(PadButton::DPadUp),
+                Action::MoveDown => Some(PadButton::DPadDown),
+                Action::MoveLeft => Some(PadButton::DPadLeft),
+                Action::MoveRight => Some(PadButton::DPadRight),
+                Action::MenuUp => Some(PadButton::DPadUp),
+                Action::MenuDown => Some(PadButton::DPadDown),
+                Action::Confirm => Some(PadButton::South),
+                Action::Interact => Some(PadButton::East),
+                Action::Wait => Some(PadButton::West),
+                Action::Inventory => Some(PadButton::North),
+                Action::Target => Some(PadButton::RightTrigger),
+                Action::Zoom => Some(PadButton::LeftTrigger),
+                Action::Cancel => Some(PadButton::Select),
+                Action::Rest => Some(PadButton::RightTrigger2),
+                Action::Explore => Some(PadButton::LeftTrigger2),
+                Action::Controls => Some(PadButton::Start),
+                _ => None,
+            };
+            bound.extend(pad.map(Binding::Pad));
+            bindings.insert(*action, bound);
+        }
+        Self(bindings)
+    }
+}
+impl Bindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        &self.0[&action]
+    }
+    /// Adds `binding` to the action, or removes it if it's already bound to it
+    pub fn toggle(&mut self, action: Action, binding: Binding) {
+        let bound = self.0.get_mut(&action).unwrap();
+        if let Some(index) = bound.iter().position(|f| *f == binding) {
+            bound.remove(index);
+        } else {
+            bound.push(binding);
+        }
+    }
+    pub fn clear(&mut self, action: Action) {
+        self.0.get_mut(&action).unwrap().clear();
+    }
+    /// Other actions in an overlapping context sharing a binding with `action`
+    pub fn conflicts(&self, action: Action) -> Vec<Action> {
+        ACTIONS
+            .iter()
+            .map(|(f, _)| *f)
+            .filter(|other| {
+                *other != action
+                    && other.context().overlaps(action.context())
+                    && self
+                        .get(*other)
+                        .iter()
+                        .any(|f| self.get(action).contains(f))
+            })
+            .collect()
+    }
+    /// Reads bindings from config file lines like `move_up = W, Up`.
+    /// Actions missing from the config keep their default bindings, and unknown names are skipped.
+    pub fn parse(config: &str) -> Self {
+        let mut bindings = Self::default();
+        for line in config.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, bound)) = line.split_once('=') else {
+                continue;
+            };
+            let Some((action, _)) = ACTIONS.iter().find(|(_, f)| *f == name.trim()) else {
+                continue;
+            };
+            bindings.0.insert(
+                *action,
+                bound
+                    .split(',')
+                    .filter_map(|f| Binding::parse(f.trim()))
+                    .collect(),
+            );
+        }
+        bindings
+    }
+    pub fn serialize(&self) -> String {
+        let mut config = String::from("# action = key, key, ...\n");
+        for (action, name) in ACTIONS {
+            let bound: Vec<String> = self.get(*action).iter().map(|f| f.name()).collect();
+            config += &format!("{name} = {}\n", bound.join(", "));
+        }
+        config
+    }
+    fn load() -> Self {
+        std::fs::read_to_string(CONFIG_PATH)
+            .map(|f| Self::parse(&f))
+            .unwrap_or_default()
+    }
+    /// Writes the bindings to the config file. Fails silently where there's no file system (web).
+    pub fn save(&self) {
+        let _ = std::fs::write(CONFIG_PATH, self.serialize());
+    }
+}
+
+static BINDINGS: LazyLock<Mutex<Bindings>> = LazyLock::new(|| Mutex::new(Bindings::load()));
+
+pub fn bindings() -> MutexGuard<'static, Bindings> {
+    BINDINGS.lock().unwrap()
+}
+/// Polls connected gamepads. Called once at the start of every frame.
+pub fn update_pad() {
+    PAD.with_borrow_mut(|f| f.update());
+}
+/// Whether any key or gamepad button was pressed this frame
+pub fn any_pressed() -> bool {
+    !get_keys_pressed().is_empty() || PAD.with_borrow(|f| f.get_pressed().is_some())
+}
+/// Whether any binding of the action was pressed this frame
+pub fn is_pressed(action: Action) -> bool {
+    bindings().get(action).iter().any(|f| f.is_pressed())
+}
+/// Whether any binding of the action is being held down
+pub fn is_down(action: Action) -> bool {
+    bindings().get(action).iter().any(|f| f.is_down())
+}
+/// Label of the action's first binding, for showing in the hud.
+/// Pad buttons are preferred while the gamepad is the last thing used.
+pub fn label(action: Action) -> String {
+    let pad_active = PAD.with_borrow(|f| f.active);
+    let bindings = bindings();
+    let bound = bindings.get(action);
+    bound
+        .iter()
+        .find(|f| matches!(f, Binding::Pad(_)) == pad_active)
+        .or(bound.first())
+        .map_or(String::from("-"), |f| f.label())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::*;
+
+    #[test]
+    fn test_config() {
+        let bindings =
+            Bindings::parse("wait = Space, h, PadWest\n# comment\nbogus = Q\npan = MouseRight");
+        assert_eq!(
+            bindings.get(Action::Wait),
+            &[
+                Binding::Key(KeyCode::Space),
+                Binding::Key(KeyCode::H),
+                Binding::Pad(PadButton::West)
+            ]
+        );
+        assert_eq!(
+            bindings.get(Action::Pan),
+            &[Binding::Mouse(MouseButton::Right)]
+        );
+        assert_eq!(
+            bindings.get(Action::Rest),
+            Bindings::default().get(Action::Rest)
+        );
+
+        let reloaded = Bindings::parse(&bindings.serialize());
+        for (action, _) in ACTIONS {
+            assert_eq!(reloaded.get(*action), bindings.get(*action));
+        }
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut bindings = Bindings::default();
+        for (action, _) in ACTIONS {
+            assert!(bindings.conflicts(*action).is_empty());
+        }
+        // the same key in a different context is fine
+        let r = Binding::Key(KeyCode::R);
+        assert!(
+            bindings.get(Action::MenuRead).contains(&r) && bindings.get(Action::Rest).contains(&r)
+        );
+
+        bindings.toggle(Action::Wait, Binding::Key(KeyCode::E));
+        assert_eq!(bindings.conflicts(Action::Wait), vec![Action::Interact]);
+        bindings.toggle(Action::Wait, Binding::Key(KeyCode::E));
+        assert!(bindings.conflicts(Action::Wait).is_empty());
+
+        // the menu has its own navigation, but confirming is used in both contexts
+        bindings.toggle(Action::MenuDown, Binding::Key(KeyCode::S));
+        assert_eq!(
+            bindings.conflicts(Action::MenuDown),
+            vec![Action::MenuSalvage]
+        );
+        bindings.toggle(Action::Confirm, Binding::Key(KeyCode::D));
+        assert_eq!(
+            bindings.conflicts(Action::Confirm),
+            vec![Action::MoveRight, Action::MenuDrop]
+        );
+    }
+}
@@ -1,5 +1,8 @@
-use crate::utils::*;
-use macroquad::prelude::*;
+use crate::{
+    ballistics::{BOLT, Projectile},
+    utils::*,
+};
+use macroquad::{prelude::*, rand::ChooseRandom};
 
 pub static ITEM_COMBINATIONS: &[([Item; 2], Item)] = &[
     (
@@ -55,23 +58,104 @@ pub static ITEM_COMBINATIONS: &[([Item; 2], Item)] = &[
         [Item::Misc(&IRON_ORE), Item::Armor(&TUNIC)],
         Item::Armor(&IRON_ARMOR),
     ),
+    (
+        [Item::Misc(&FIBER), Item::Misc(&STONE)],
+        Item::Ammo(&STONE_ARROW),
+    ),
+    (
+        [Item::Misc(&STICK), Item::Misc(&BONE)],
+        Item::Ammo(&BONE_ARROW),
+    ),
+    (
+        [Item::Misc(&STICK), Item::Misc(&IRON_ORE)],
+        Item::Ammo(&IRON_ARROW),
+    ),
+    (
+        [Item::Ammo(&STONE_ARROW), Item::Misc(&POISON_MUSHROOM)],
+        Item::Ammo(&POISON_ARROW),
+    ),
+];
+
+/// Materials which restore a damaged item to full durability when combined with it.
+pub static REPAIR_RECIPES: &[(Item, Item)] = &[
+    (Item::Weapon(&STONE_SPEAR), Item::Misc(&STONE)),
+    (Item::Weapon(&BONE_SPEAR), Item::Misc(&BONE)),
+    (Item::Weapon(&STONE_AXE), Item::Misc(&STONE)),
+    (Item::Weapon(&SHORTBOW), Item::Misc(&FIBER)),
+    (Item::Weapon(&LONGBOW), Item::Misc(&FIBER)),
+    (Item::Weapon(&IRON_SWORD), Item::Misc(&IRON_ORE)),
+    (Item::Weapon(&IRON_BOW), Item::Misc(&IRON_ORE)),
+    (Item::Armor(&TUNIC), Item::Misc(&FIBER)),
+    (Item::Armor(&IRON_ARMOR), Item::Misc(&IRON_ORE)),
 ];
 
-pub fn combine(a: Item, b: Item) -> Item {
+fn can_repair(item: &ItemInstance, material: &ItemInstance) -> bool {
+    item.is_damaged()
+        && REPAIR_RECIPES
+            .iter()
+            .any(|(target, with)| *target == item.item && *with == material.item)
+}
+
+/// Crafts or repairs an item, along with what's left of an ammo ingredient's stack
+pub fn combine(a: ItemInstance, b: ItemInstance) -> (ItemInstance, Option<ItemInstance>) {
     for (combination, result) in ITEM_COMBINATIONS {
-        if (combination[0] == a && combination[1] == b)
-            || combination[1] == a && combination[0] == b
+        if (combination[0] == a.item && combination[1] == b.item)
+            || combination[1] == a.item && combination[0] == b.item
         {
-            return *result;
+            let mut instance = ItemInstance::new(*result);
+            let mut leftover = None;
+            if let Item::Ammo(_) = result {
+                instance.count = ARROWS_PER_CRAFT;
+                // ammo made from a stack of ammo converts up to a craft's worth of it
+                if let Some(mut stack) =
+                    [a, b].into_iter().find(|f| matches!(f.item, Item::Ammo(_)))
+                {
+                    instance.count = stack.count.min(ARROWS_PER_CRAFT);
+                    stack.count -= instance.count;
+                    leftover = (stack.count > 0).then_some(stack);
+                }
+            }
+            return (instance, leftover);
         }
     }
+    if can_repair(&a, &b) {
+        return (a.repaired(), None);
+    }
+    if can_repair(&b, &a) {
+        return (b.repaired(), None);
+    }
     panic!("no combination for these items exist!")
 }
 
-pub fn get_combinable(items: &[Option<Item>], index: usize) -> Vec<usize> {
-    if items[index].is_none() {
+/// Returns the two ingredients an item is crafted from, if it has a recipe.
+pub fn get_ingredients(item: Item) -> Option<[Item; 2]> {
+    ITEM_COMBINATIONS
+        .iter()
+        .find(|(_, result)| *result == item)
+        .map(|(combination, _)| *combination)
+}
+
+/// Breaks a crafted item back into its ingredients.
+///
+/// Each ingredient has a `loss` chance of being destroyed, but at least one is always recovered.
+pub fn salvage(item: Item, loss: f32) -> Vec<Item> {
+    let Some(ingredients) = get_ingredients(item) else {
         return Vec::new();
+    };
+    let mut salvaged: Vec<Item> = ingredients
+        .into_iter()
+        .filter(|_| rand::gen_range(0.0, 1.0) >= loss)
+        .collect();
+    if salvaged.is_empty() {
+        salvaged.push(ingredients[rand::gen_range(0, 2)]);
     }
+    salvaged
+}
+
+pub fn get_combinable(items: &[Option<ItemInstance>], index: usize) -> Vec<usize> {
+    let Some(target) = &items[index] else {
+        return Vec::new();
+    };
     let mut combinable = Vec::new();
     for (i, item) in items.iter().enumerate() {
         if i == index {
@@ -81,12 +165,15 @@ pub fn get_combinable(items: &[Option<Item>], index: usize) -> Vec<usize> {
             continue;
         };
         for (combination, _) in ITEM_COMBINATIONS {
-            if (&combination[0] == item && combination[1] == items[index].unwrap())
-                || &combination[1] == item && combination[0] == items[index].unwrap()
+            if (combination[0] == item.item && combination[1] == target.item)
+                || combination[1] == item.item && combination[0] == target.item
             {
                 combinable.push(i);
             }
         }
+        if !combinable.contains(&i) && (can_repair(item, target) || can_repair(target, item)) {
+            combinable.push(i);
+        }
     }
     combinable
 }
@@ -94,6 +181,8 @@ pub fn get_combinable(items: &[Option<Item>], index: usize) -> Vec<usize> {
 #[derive(Clone, PartialEq)]
 pub struct Armor {
     pub block_chance: f32,
+    /// Amount of blocks before the armor breaks, or `None` if it never does
+    pub durability: Option<u16>,
     pub sprite_x: f32,
     pub sprite_y: f32,
     pub name: &'static str,
@@ -103,18 +192,63 @@ impl Armor {
         format!("Block Chance: {}", self.block_chance)
     }
 }
+/// How the distance to a target is measured when checking a weapon's range
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RangeMetric {
+    /// Steps it takes to walk there, so reach matches movement. This is the chebyshev distance
+    /// with eight-way movement, and the manhattan distance without.
+    Grid,
+    /// Straight line distance, rounded down
+    Euclidean,
+}
+impl RangeMetric {
+    pub fn distance(self, from: (usize, usize), to: (usize, usize), eight_way: bool) -> usize {
+        let (dx, dy) = (from.0.abs_diff(to.0), from.1.abs_diff(to.1));
+        match self {
+            RangeMetric::Grid if eight_way => dx.max(dy),
+            RangeMetric::Grid => dx + dy,
+            RangeMetric::Euclidean => ((dx * dx + dy * dy) as f32).sqrt() as usize,
+        }
+    }
+}
 #[derive(Clone, PartialEq)]
 pub struct Weapon {
     pub attack_range: std::ops::Range<usize>,
+    pub range_metric: RangeMetric,
     pub base_damage: f32,
     pub sprite_x: f32,
     pub sprite_y: f32,
     pub name: &'static str,
     pub fires_particle: Option<(f32, f32)>,
     pub throwable: Option<(f32, Vec2)>,
+    /// Amount of hits before the weapon breaks, or `None` if it never does
+    pub durability: Option<u16>,
+    /// Whether each attack consumes ammo from the quiver
+    pub uses_ammo: bool,
+    /// How the fired particle travels, for ranged weapons
+    pub projectile: Projectile,
+    /// Status effect applied to enemies hit by this weapon
+    pub on_hit: Option<StatusEffect>,
+    /// Amount of tiles enemies hit are knocked back
+    pub knockback: usize,
 }
 impl Weapon {
+    pub fn distance(&self, from: (usize, usize), to: (usize, usize), eight_way: bool) -> usize {
+        self.range_metric.distance(from, to, eight_way)
+    }
+    pub fn in_range(&self, from: (usize, usize), to: (usize, usize), eight_way: bool) -> bool {
+        self.attack_range
+            .contains(&self.distance(from, to, eight_way))
+    }
     fn get_desc(&self) -> String {
+        let desc = self.get_stats_desc();
+        if self.knockback > 0 {
+            format!("{desc}\nKNOCKBACK: {}", self.knockback)
+        } else {
+            desc
+        }
+    }
+    fn get_stats_desc(&self) -> String {
         if let Some((dmg, _)) = self.throwable {
             format!(
                 "DMG: {}     THROW DMG: {}\nRANGE: {}",
@@ -133,93 +267,156 @@ impl Weapon {
 }
 pub const MELEE: Weapon = Weapon {
     attack_range: 1..2,
+    range_metric: RangeMetric::Grid,
     base_damage: 1.0,
     sprite_x: 0.0,
     sprite_y: 0.0,
     name: "melee",
     fires_particle: None,
     throwable: None,
+    durability: None,
+    on_hit: None,
+    uses_ammo: false,
+    projectile: BOLT,
+    knockback: 0,
 };
 pub const DAGGER: Weapon = Weapon {
     attack_range: 1..2,
+    range_metric: RangeMetric::Grid,
     base_damage: 2.5,
     sprite_x: 1.0,
     sprite_y: 0.0,
     name: "dagger",
     fires_particle: None,
     throwable: None,
+    durability: None,
+    on_hit: None,
+    uses_ammo: false,
+    projectile: BOLT,
+    knockback: 0,
 };
 pub const SHORTBOW: Weapon = Weapon {
     attack_range: 2..4,
+    range_metric: RangeMetric::Euclidean,
     base_damage: 1.0,
     sprite_x: 2.0,
     sprite_y: 0.0,
     name: "shortbow",
     fires_particle: Some((0.0, 0.0)),
     throwable: None,
+    durability: Some(20),
+    on_hit: None,
+    uses_ammo: true,
+    projectile: BOLT,
+    knockback: 0,
 };
 pub const LONGBOW: Weapon = Weapon {
     attack_range: 2..5,
+    range_metric: RangeMetric::Euclidean,
     base_damage: 2.0,
     sprite_x: 6.0,
     sprite_y: 0.0,
     name: "longbow",
     fires_particle: Some((0.0, 0.0)),
     throwable: None,
+    durability: Some(30),
+    on_hit: None,
+    uses_ammo: true,
+    projectile: BOLT,
+    knockback: 0,
 };
 pub const SPELLBOOK: Weapon = Weapon {
     attack_range: 2..4,
+    range_metric: RangeMetric::Euclidean,
     base_damage: 5.0,
     sprite_x: 3.0,
     sprite_y: 0.0,
     name: "spellbook",
     fires_particle: Some((3.0, 0.0)),
     throwable: None,
+    durability: None,
+    on_hit: None,
+    uses_ammo: false,
+    projectile: BOLT,
+    knockback: 0,
 };
 pub const SUPER_SPELLBOOK: Weapon = Weapon {
     attack_range: 2..5,
+    range_metric: RangeMetric::Euclidean,
     base_damage: 6.0,
     sprite_x: 10.0,
     sprite_y: 0.0,
     name: "super spellbook",
     fires_particle: Some((3.0, 1.0)),
     throwable: None,
+    durability: None,
+    on_hit: None,
+    uses_ammo: false,
+    projectile: Projectile {
+        splash: 1.0,
+        ..BOLT
+    },
+    knockback: 1,
 };
 pub const STONE_SPEAR: Weapon = Weapon {
     attack_range: 1..2,
+    range_metric: RangeMetric::Grid,
     sprite_x: 4.0,
     sprite_y: 0.0,
     base_damage: 2.0,
     fires_particle: None,
     name: "stone spear",
     throwable: Some((6.0, vec2(1.0, 0.0))),
+    durability: Some(15),
+    on_hit: None,
+    uses_ammo: false,
+    projectile: BOLT,
+    knockback: 0,
 };
 pub const IRON_SWORD: Weapon = Weapon {
     attack_range: 1..2,
+    range_metric: RangeMetric::Grid,
     sprite_x: 7.0,
     sprite_y: 0.0,
     base_damage: 8.0,
     fires_particle: None,
     name: "iron sword",
     throwable: Some((6.0, vec2(1.0, 0.0))),
+    durability: Some(40),
+    on_hit: None,
+    uses_ammo: false,
+    projectile: BOLT,
+    knockback: 1,
 };
 pub const IRON_BOW: Weapon = Weapon {
     attack_range: 2..4,
+    range_metric: RangeMetric::Euclidean,
     sprite_x: 9.0,
     sprite_y: 0.0,
     base_damage: 5.0,
     fires_particle: Some((0.0, 0.0)),
     name: "iron bow",
     throwable: None,
+    durability: Some(40),
+    on_hit: None,
+    uses_ammo: true,
+    projectile: BOLT,
+    knockback: 0,
 };
 pub const STONE_AXE: Weapon = Weapon {
     attack_range: 1..2,
+    range_metric: RangeMetric::Grid,
     sprite_x: 8.0,
     sprite_y: 0.0,
     base_damage: 5.0,
     fires_particle: None,
     name: "stone axe",
     throwable: Some((2.0, vec2(6.0, 0.0))),
+    durability: Some(25),
+    on_hit: None,
+    uses_ammo: false,
+    projectile: BOLT,
+    knockback: 1,
 };
 pub const BONE_SPEAR: Weapon = Weapon {
     sprite_x: 5.0,
@@ -232,16 +429,38 @@ pub const IRON_ARMOR: Armor = Armor {
     sprite_x: 0.0,
     sprite_y: 1.0,
     name: "iron armor",
+    durability: Some(30),
 };
 pub const TUNIC: Armor = Armor {
     block_chance: 0.3,
     sprite_x: 2.0,
     sprite_y: 1.0,
     name: "leather tunic",
+    durability: Some(15),
 };
 #[derive(Clone, Copy, PartialEq, Hash, Eq)]
 pub enum StatusEffect {
     Poison,
+    /// Skips the affected enemy's turns
+    Frozen,
+    /// Speeds up health regeneration
+    Regeneration,
+}
+impl StatusEffect {
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusEffect::Poison => "poisoned",
+            StatusEffect::Frozen => "frozen",
+            StatusEffect::Regeneration => "regenerating",
+        }
+    }
+    /// Turns a single application of the effect lasts
+    pub fn duration(self) -> u16 {
+        match self {
+            StatusEffect::Regeneration => 15,
+            _ => 3,
+        }
+    }
 }
 #[derive(Clone, PartialEq)]
 pub struct MiscItem {
@@ -251,14 +470,23 @@ pub struct MiscItem {
     desc: &'static str,
     throwable: Option<(f32, Vec2)>,
     pub consumable: Option<(f32, Option<StatusEffect>)>,
+    /// Hunger restored when eaten
+    pub nutrition: u16,
+    /// Turns until the item rots, and what it rots into
+    pub rots: Option<(u16, &'static MiscItem)>,
+    /// Colour the sprite is tinted with, untinted when `None`
+    tint: Option<Color>,
 }
 impl MiscItem {
     fn get_desc(&self) -> String {
+        let mut desc = self.desc.to_string();
         if let Some((dmg, _)) = self.throwable {
-            format!("{}\nTHROW DMG: {dmg}", self.desc)
-        } else {
-            self.desc.to_string()
+            desc += &format!("\nTHROW DMG: {dmg}");
         }
+        if self.nutrition > 0 {
+            desc += &format!("\nFOOD: {}", self.nutrition);
+        }
+        desc
     }
 }
 pub const STICK: MiscItem = MiscItem {
@@ -268,6 +496,9 @@ pub const STICK: MiscItem = MiscItem {
     desc: "a cool stick",
     throwable: None,
     consumable: None,
+    nutrition: 0,
+    rots: None,
+    tint: None,
 };
 pub const BONE: MiscItem = MiscItem {
     sprite_x: 3.0,
@@ -276,6 +507,9 @@ pub const BONE: MiscItem = MiscItem {
     desc: "a real bone",
     throwable: None,
     consumable: None,
+    nutrition: 0,
+    rots: None,
+    tint: None,
 };
 pub const STONE: MiscItem = MiscItem {
     sprite_x: 1.0,
@@ -284,6 +518,9 @@ pub const STONE: MiscItem = MiscItem {
     desc: "a small stone",
     throwable: Some((3.0, vec2(2.0, 0.0))),
     consumable: None,
+    nutrition: 0,
+    rots: None,
+    tint: None,
 };
 pub const AXE_BLADE: MiscItem = MiscItem {
     sprite_x: 10.0,
@@ -292,6 +529,9 @@ pub const AXE_BLADE: MiscItem = MiscItem {
     desc: "a stone axe blade",
     throwable: Some((5.0, vec2(5.0, 0.0))),
     consumable: None,
+    nutrition: 0,
+    rots: None,
+    tint: None,
 };
 pub const FLESH: MiscItem = MiscItem {
     sprite_x: 2.0,
@@ -300,6 +540,20 @@ pub const FLESH: MiscItem = MiscItem {
     desc: "consumable flesh",
     throwable: None,
     consumable: Some((2.0, None)),
+    nutrition: 250,
+    rots: Some((FLESH_ROT_TURNS, &ROTTEN_FLESH)),
+    tint: None,
+};
+pub const ROTTEN_FLESH: MiscItem = MiscItem {
+    sprite_x: 2.0,
+    sprite_y: 2.0,
+    name: "rotten flesh",
+    desc: "smells awful",
+    throwable: None,
+    consumable: Some((0.0, Some(StatusEffect::Poison))),
+    nutrition: 100,
+    rots: None,
+    tint: Some(Color::from_hex(0x9a9a5a)),
 };
 pub const RATIONS: MiscItem = MiscItem {
     sprite_x: 7.0,
@@ -308,6 +562,9 @@ pub const RATIONS: MiscItem = MiscItem {
     desc: "yummy rations",
     throwable: None,
     consumable: Some((3.0, None)),
+    nutrition: 600,
+    rots: None,
+    tint: None,
 };
 pub const LEAF: MiscItem = MiscItem {
     sprite_x: 4.0,
@@ -316,6 +573,9 @@ pub const LEAF: MiscItem = MiscItem {
     desc: "can be crafted into fiber",
     throwable: None,
     consumable: None,
+    nutrition: 0,
+    rots: None,
+    tint: None,
 };
 pub const FIBER: MiscItem = MiscItem {
     sprite_x: 5.0,
@@ -324,6 +584,9 @@ pub const FIBER: MiscItem = MiscItem {
     desc: "useful for crafting clothes",
     throwable: None,
     consumable: None,
+    nutrition: 0,
+    rots: None,
+    tint: None,
 };
 pub const POISON_MUSHROOM: MiscItem = MiscItem {
     sprite_x: 6.0,
@@ -332,6 +595,9 @@ pub const POISON_MUSHROOM: MiscItem = MiscItem {
     desc: "maybe throw on your foes?",
     throwable: Some((0.0, vec2(4.0, 0.0))),
     consumable: Some((0.0, Some(StatusEffect::Poison))),
+    nutrition: 0,
+    rots: None,
+    tint: None,
 };
 pub const POISON_ROCK: MiscItem = MiscItem {
     sprite_x: 8.0,
@@ -340,6 +606,9 @@ pub const POISON_ROCK: MiscItem = MiscItem {
     desc: "even hurtyer rock!",
     throwable: Some((3.0, vec2(4.0, 0.0))),
     consumable: Some((0.0, Some(StatusEffect::Poison))),
+    nutrition: 0,
+    rots: None,
+    tint: None,
 };
 pub const SLIME_GEL: MiscItem = MiscItem {
     sprite_x: 9.0,
@@ -348,6 +617,9 @@ pub const SLIME_GEL: MiscItem = MiscItem {
     desc: "gooey substance!",
     throwable: None,
     consumable: Some((1.0, None)),
+    nutrition: 100,
+    rots: None,
+    tint: None,
 };
 pub const IRON_ORE: MiscItem = MiscItem {
     sprite_x: 11.0,
@@ -356,6 +628,9 @@ pub const IRON_ORE: MiscItem = MiscItem {
     desc: "useable for blades!",
     throwable: None,
     consumable: None,
+    nutrition: 0,
+    rots: None,
+    tint: None,
 };
 pub const IRON_BLADE: MiscItem = MiscItem {
     sprite_x: 12.0,
@@ -364,12 +639,312 @@ pub const IRON_BLADE: MiscItem = MiscItem {
     desc: "sharp as heck",
     throwable: Some((4.0, vec2(7.0, 0.0))),
     consumable: None,
+    nutrition: 0,
+    rots: None,
+    tint: None,
 };
+/// Arrows fired by weapons which use ammo
+#[derive(PartialEq)]
+pub struct Ammo {
+    sprite_x: f32,
+    sprite_y: f32,
+    /// Tint applied to the sprite, to tell arrows apart
+    color: Color,
+    name: &'static str,
+    pub damage: f32,
+    /// Chance for the arrow to be dropped where it lands
+    pub recover_chance: f32,
+    pub on_hit: Option<StatusEffect>,
+    /// Amount of extra enemies the arrow passes through
+    pub pierce: usize,
+}
+impl Ammo {
+    fn get_desc(&self) -> String {
+        if self.pierce > 0 {
+            format!("DMG: +{}\nPIERCES {}", self.damage, self.pierce)
+        } else {
+            format!("DMG: +{}", self.damage)
+        }
+    }
+}
+pub const STONE_ARROW: Ammo = Ammo {
+    sprite_x: 0.0,
+    sprite_y: 2.0,
+    color: Color::from_hex(0x8b9bb4),
+    name: "stone arrow",
+    damage: 0.0,
+    recover_chance: 0.5,
+    on_hit: None,
+    pierce: 0,
+};
+pub const BONE_ARROW: Ammo = Ammo {
+    color: Color::from_hex(0xead4aa),
+    name: "bone arrow",
+    damage: 1.0,
+    recover_chance: 0.6,
+    ..STONE_ARROW
+};
+pub const IRON_ARROW: Ammo = Ammo {
+    color: Color::from_hex(0x5a6988),
+    name: "iron arrow",
+    damage: 3.0,
+    recover_chance: 0.75,
+    pierce: 1,
+    ..STONE_ARROW
+};
+pub const POISON_ARROW: Ammo = Ammo {
+    color: Color::from_hex(0x63c74d),
+    name: "poison arrow",
+    on_hit: Some(StatusEffect::Poison),
+    recover_chance: 0.25,
+    ..STONE_ARROW
+};
+#[derive(Clone, Copy, PartialEq)]
+pub enum PotionEffect {
+    Heal(f32),
+    Status(StatusEffect),
+    /// Removes all status effects
+    Cure,
+}
+#[derive(PartialEq)]
+pub struct Potion {
+    sprite_x: f32,
+    sprite_y: f32,
+    name: &'static str,
+    desc: &'static str,
+    pub effect: PotionEffect,
+}
+pub const HEALING_POTION: Potion = Potion {
+    sprite_x: 0.0,
+    sprite_y: 3.0,
+    name: "healing potion",
+    desc: "heals a fair bit",
+    effect: PotionEffect::Heal(8.0),
+};
+pub const GREATER_HEALING_POTION: Potion = Potion {
+    name: "greater healing potion",
+    desc: "heals a whole lot",
+    effect: PotionEffect::Heal(20.0),
+    ..HEALING_POTION
+};
+pub const POISON_POTION: Potion = Potion {
+    name: "poison potion",
+    desc: "why would you drink this",
+    effect: PotionEffect::Status(StatusEffect::Poison),
+    ..HEALING_POTION
+};
+pub const CURE_POTION: Potion = Potion {
+    name: "cure potion",
+    desc: "cures all status effects",
+    effect: PotionEffect::Cure,
+    ..HEALING_POTION
+};
+pub const REGENERATION_POTION: Potion = Potion {
+    name: "regeneration potion",
+    desc: "slowly mends wounds",
+    effect: PotionEffect::Status(StatusEffect::Regeneration),
+    ..HEALING_POTION
+};
+pub static POTIONS: &[&Potion] = &[
+    &HEALING_POTION,
+    &GREATER_HEALING_POTION,
+    &POISON_POTION,
+    &CURE_POTION,
+    &REGENERATION_POTION,
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ScrollEffect {
+    /// Lets the player pick an item to identify
+    Identify,
+    /// Teleports the player to a random tile
+    Teleport,
+    /// Reveals the layout of the whole floor
+    MagicMapping,
+    /// Damages all enemies within radius of the target tile, knocking them away from it
+    Fireball {
+        damage: f32,
+        radius: f32,
+        knockback: usize,
+        range: f32,
+    },
+    /// Freezes all enemies within radius of the target tile
+    Freeze { radius: f32, range: f32 },
+    /// Teleports the player to a chosen tile within range
+    Blink { range: f32 },
+}
+impl ScrollEffect {
+    /// How far away the target tile can be, for scrolls that need one
+    pub fn range(self) -> Option<f32> {
+        match self {
+            ScrollEffect::Fireball { range, .. }
+            | ScrollEffect::Freeze { range, .. }
+            | ScrollEffect::Blink { range } => Some(range),
+            _ => None,
+        }
+    }
+    /// Whether the scroll needs a target tile to be chosen when read
+    pub fn is_aimed(self) -> bool {
+        self.range().is_some()
+    }
+}
+#[derive(PartialEq)]
+pub struct Scroll {
+    sprite_x: f32,
+    sprite_y: f32,
+    name: &'static str,
+    desc: &'static str,
+    pub effect: ScrollEffect,
+}
+pub const IDENTIFY_SCROLL: Scroll = Scroll {
+    sprite_x: 1.0,
+    sprite_y: 3.0,
+    name: "scroll of identify",
+    desc: "reveals what an item is",
+    effect: ScrollEffect::Identify,
+};
+pub const TELEPORT_SCROLL: Scroll = Scroll {
+    name: "scroll of teleport",
+    desc: "takes you somewhere else",
+    effect: ScrollEffect::Teleport,
+    ..IDENTIFY_SCROLL
+};
+pub const MAGIC_MAPPING_SCROLL: Scroll = Scroll {
+    name: "scroll of magic mapping",
+    desc: "reveals the floor layout",
+    effect: ScrollEffect::MagicMapping,
+    ..IDENTIFY_SCROLL
+};
+pub const FIREBALL_SCROLL: Scroll = Scroll {
+    name: "scroll of fireball",
+    desc: "burns everything nearby\nthe chosen tile",
+    effect: ScrollEffect::Fireball {
+        damage: 6.0,
+        radius: 1.5,
+        knockback: 1,
+        range: 6.0,
+    },
+    ..IDENTIFY_SCROLL
+};
+pub const FREEZE_SCROLL: Scroll = Scroll {
+    name: "scroll of freeze",
+    desc: "freezes enemies nearby\nthe chosen tile",
+    effect: ScrollEffect::Freeze {
+        radius: 1.5,
+        range: 6.0,
+    },
+    ..IDENTIFY_SCROLL
+};
+pub const BLINK_SCROLL: Scroll = Scroll {
+    name: "scroll of blink",
+    desc: "teleports you to a\nnearby tile",
+    effect: ScrollEffect::Blink { range: 4.0 },
+    ..IDENTIFY_SCROLL
+};
+pub static SCROLLS: &[&Scroll] = &[
+    &IDENTIFY_SCROLL,
+    &TELEPORT_SCROLL,
+    &MAGIC_MAPPING_SCROLL,
+    &FIREBALL_SCROLL,
+    &FREEZE_SCROLL,
+    &BLINK_SCROLL,
+];
+
+/// Names and sprite tints potions and scrolls are disguised as until identified.
+pub static POTION_APPEARANCES: &[(&str, Color)] = &[
+    ("murky", Color::from_hex(0x8b6d4f)),
+    ("bubbling", Color::from_hex(0x63c74d)),
+    ("violet", Color::from_hex(0xb55088)),
+    ("golden", Color::from_hex(0xfee761)),
+    ("crimson", Color::from_hex(0xe43b44)),
+    ("cloudy", Color::from_hex(0xc0cbdc)),
+];
+pub static SCROLL_APPEARANCES: &[(&str, Color)] = &[
+    ("dusty", Color::from_hex(0xc0cbdc)),
+    ("runed", Color::from_hex(0x2ce8f5)),
+    ("tattered", Color::from_hex(0xe4a672)),
+    ("bloodied", Color::from_hex(0xe43b44)),
+    ("glowing", Color::from_hex(0xfee761)),
+    ("sooty", Color::from_hex(0x5a6988)),
+    ("mossy", Color::from_hex(0x63c74d)),
+];
+
+/// Tracks the per-run mapping of potions and scrolls to their appearances,
+/// and which of them the player has identified.
+pub struct Identification {
+    potion_appearances: Vec<usize>,
+    scroll_appearances: Vec<usize>,
+    identified: Vec<Item>,
+}
+impl Default for Identification {
+    fn default() -> Self {
+        let mut potion_appearances: Vec<usize> = (0..POTION_APPEARANCES.len()).collect();
+        let mut scroll_appearances: Vec<usize> = (0..SCROLL_APPEARANCES.len()).collect();
+        potion_appearances.shuffle();
+        scroll_appearances.shuffle();
+        Self {
+            potion_appearances,
+            scroll_appearances,
+            identified: Vec::new(),
+        }
+    }
+}
+impl Identification {
+    fn appearance(&self, item: &Item) -> Option<(&'static str, Color)> {
+        match item {
+            Item::Potion(potion) => {
+                let index = POTIONS.iter().position(|f| f == potion)?;
+                Some(POTION_APPEARANCES[self.potion_appearances[index]])
+            }
+            Item::Scroll(scroll) => {
+                let index = SCROLLS.iter().position(|f| f == scroll)?;
+                Some(SCROLL_APPEARANCES[self.scroll_appearances[index]])
+            }
+            _ => None,
+        }
+    }
+    pub fn is_identified(&self, item: &Item) -> bool {
+        self.appearance(item).is_none() || self.identified.contains(item)
+    }
+    pub fn identify(&mut self, item: Item) {
+        if !self.is_identified(&item) {
+            self.identified.push(item);
+        }
+    }
+    /// Name of the item as the player knows it
+    pub fn get_name(&self, item: &ItemInstance) -> String {
+        if self.is_identified(&item.item) {
+            return item.get_name();
+        }
+        let (appearance, _) = self.appearance(&item.item).unwrap();
+        match item.item {
+            Item::Scroll(_) => format!("{appearance} scroll"),
+            _ => format!("{appearance} potion"),
+        }
+    }
+    /// Description of the item as the player knows it
+    pub fn get_desc(&self, item: &ItemInstance) -> String {
+        if self.is_identified(&item.item) {
+            item.get_desc()
+        } else {
+            String::from("unidentified.\nuse it to find out what it does")
+        }
+    }
+    /// Colour the item's sprite should be tinted with
+    pub fn tint(&self, item: &Item) -> Color {
+        self.appearance(item)
+            .map_or(item.tint(), |(_, color)| color)
+    }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum Item {
     Weapon(&'static Weapon),
     Armor(&'static Armor),
     Misc(&'static MiscItem),
+    Potion(&'static Potion),
+    Scroll(&'static Scroll),
+    Ammo(&'static Ammo),
 }
 impl Item {
     pub fn get_sprite(&self) -> Vec2 {
@@ -377,6 +952,9 @@ impl Item {
             Item::Weapon(weapon) => vec2(weapon.sprite_x, weapon.sprite_y),
             Item::Armor(armor) => vec2(armor.sprite_x, armor.sprite_y),
             Item::Misc(misc_item) => vec2(misc_item.sprite_x, misc_item.sprite_y),
+            Item::Potion(potion) => vec2(potion.sprite_x, potion.sprite_y),
+            Item::Scroll(scroll) => vec2(scroll.sprite_x, scroll.sprite_y),
+            Item::Ammo(ammo) => vec2(ammo.sprite_x, ammo.sprite_y),
         }
     }
     pub fn get_name(&self) -> &'static str {
@@ -384,6 +962,9 @@ impl Item {
             Item::Weapon(weapon) => weapon.name,
             Item::Armor(armor) => armor.name,
             Item::Misc(misc_item) => misc_item.name,
+            Item::Potion(potion) => potion.name,
+            Item::Scroll(scroll) => scroll.name,
+            Item::Ammo(ammo) => ammo.name,
         }
     }
     pub fn get_desc(&self) -> String {
@@ -391,13 +972,410 @@ impl Item {
             Item::Weapon(weapon) => weapon.get_desc(),
             Item::Armor(armor) => armor.get_desc(),
             Item::Misc(misc_item) => misc_item.get_desc(),
+            Item::Potion(potion) => potion.desc.to_string(),
+            Item::Scroll(scroll) => scroll.desc.to_string(),
+            Item::Ammo(ammo) => ammo.get_desc(),
         }
     }
     pub fn throwable(&self) -> Option<(f32, Vec2)> {
         match &self {
             Item::Weapon(weapon) => weapon.throwable,
-            Item::Armor(_) => None,
             Item::Misc(misc_item) => misc_item.throwable,
+            _ => None,
+        }
+    }
+    pub fn max_durability(&self) -> Option<u16> {
+        match &self {
+            Item::Weapon(weapon) => weapon.durability,
+            Item::Armor(armor) => armor.durability,
+            _ => None,
+        }
+    }
+    pub fn tint(&self) -> Color {
+        match &self {
+            Item::Ammo(ammo) => ammo.color,
+            Item::Misc(misc) => misc.tint.unwrap_or(WHITE),
+            _ => WHITE,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+impl Rarity {
+    /// How many affixes an item of this rarity rolls
+    pub fn affix_count(self) -> usize {
+        match self {
+            Rarity::Common => 0,
+            Rarity::Uncommon => 1,
+            Rarity::Rare => 2,
+            Rarity::Legendary => 3,
+        }
+    }
+    pub fn color(self) -> Color {
+        match self {
+            Rarity::Common => WHITE,
+            Rarity::Uncommon => Color::from_hex(0x63c74d),
+            Rarity::Rare => Color::from_hex(0x2ce8f5),
+            Rarity::Legendary => GOLD,
+        }
+    }
+}
+
+#[derive(PartialEq)]
+pub enum AffixTarget {
+    Weapon,
+    RangedWeapon,
+    Armor,
+}
+
+/// A modifier rolled onto an item instance, altering its stats.
+#[derive(PartialEq)]
+pub struct Affix {
+    pub prefix: &'static str,
+    pub target: AffixTarget,
+    pub damage: f32,
+    pub range: usize,
+    pub block_chance: f32,
+    pub on_hit: Option<StatusEffect>,
+    pub knockback: usize,
+    /// Extra health regenerated every turn while equipped
+    pub regen: f32,
+}
+const NO_AFFIX: Affix = Affix {
+    prefix: "",
+    target: AffixTarget::Weapon,
+    damage: 0.0,
+    range: 0,
+    block_chance: 0.0,
+    on_hit: None,
+    knockback: 0,
+    regen: 0.0,
+};
+pub const SHARP: Affix = Affix {
+    prefix: "sharp",
+    damage: 1.5,
+    ..NO_AFFIX
+};
+pub const HEAVY: Affix = Affix {
+    prefix: "heavy",
+    damage: 3.0,
+    knockback: 1,
+    ..NO_AFFIX
+};
+pub const VENOMOUS: Affix = Affix {
+    prefix: "venomous",
+    on_hit: Some(StatusEffect::Poison),
+    ..NO_AFFIX
+};
+pub const FARSIGHTED: Affix = Affix {
+    prefix: "farsighted",
+    target: AffixTarget::RangedWeapon,
+    range: 1,
+    ..NO_AFFIX
+};
+pub const STURDY: Affix = Affix {
+    prefix: "sturdy",
+    target: AffixTarget::Armor,
+    block_chance: 0.1,
+    ..NO_AFFIX
+};
+pub const WARDING: Affix = Affix {
+    prefix: "warding",
+    target: AffixTarget::Armor,
+    block_chance: 0.15,
+    ..NO_AFFIX
+};
+pub const MENDING: Affix = Affix {
+    prefix: "mending",
+    target: AffixTarget::Armor,
+    regen: 0.05,
+    ..NO_AFFIX
+};
+pub static AFFIXES: &[&Affix] = &[
+    &SHARP,
+    &HEAVY,
+    &VENOMOUS,
+    &FARSIGHTED,
+    &STURDY,
+    &WARDING,
+    &MENDING,
+];
+
+impl Affix {
+    fn applies_to(&self, item: &Item) -> bool {
+        match (&self.target, item) {
+            (AffixTarget::Weapon, Item::Weapon(_)) => true,
+            (AffixTarget::RangedWeapon, Item::Weapon(weapon)) => weapon.attack_range.start > 1,
+            (AffixTarget::Armor, Item::Armor(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A single item in an inventory or on the ground,
+/// carrying the state that differs between items of the same kind.
+#[derive(Clone, PartialEq)]
+pub struct ItemInstance {
+    pub item: Item,
+    /// Remaining durability, `None` for unbreakable items
+    pub durability: Option<u16>,
+    pub rarity: Rarity,
+    pub affixes: Vec<&'static Affix>,
+    /// Size of the stack, only ever above one for ammo
+    pub count: u16,
+    /// Turns the item has spent rotting
+    pub age: u16,
+}
+impl ItemInstance {
+    pub fn new(item: Item) -> Self {
+        Self {
+            item,
+            durability: item.max_durability(),
+            rarity: Rarity::Common,
+            affixes: Vec::new(),
+            count: 1,
+            age: 0,
+        }
+    }
+    /// Creates an item with random affixes, the amount of which depends on rarity.
+    ///
+    /// Items which no affixes apply to are always common.
+    pub fn roll(item: Item, rarity: Rarity) -> Self {
+        let mut candidates: Vec<&'static Affix> = AFFIXES
+            .iter()
+            .copied()
+            .filter(|f| f.applies_to(&item))
+            .collect();
+        let mut instance = Self::new(item);
+        for _ in 0..rarity.affix_count() {
+            if candidates.is_empty() {
+                break;
+            }
+            instance
+                .affixes
+                .push(candidates.remove(rand::gen_range(0, candidates.len())));
+        }
+        if !instance.affixes.is_empty() {
+            instance.rarity = rarity;
+        }
+        if let Item::Ammo(_) = item {
+            instance.count = rand::gen_range(2, ARROWS_PER_CRAFT + 1);
+        }
+        instance
+    }
+    /// Advances rotting by a turn, turning the item into its rotten form once it's rotted away
+    pub fn age(&mut self) {
+        if let Item::Misc(MiscItem {
+            rots: Some((turns, rotten)),
+            ..
+        }) = self.item
+        {
+            self.age += 1;
+            if self.age >= *turns {
+                *self = ItemInstance::new(Item::Misc(rotten));
+            }
+        }
+    }
+    /// Whether `other` can be merged into this stack
+    pub fn stacks_with(&self, other: &ItemInstance) -> bool {
+        matches!(self.item, Item::Ammo(_)) && self.item == other.item
+    }
+    pub fn get_name(&self) -> String {
+        let mut name = String::new();
+        for affix in &self.affixes {
+            name += affix.prefix;
+            name += " ";
+        }
+        name += self.item.get_name();
+        if self.count > 1 {
+            name += &format!(" x{}", self.count);
+        }
+        name
+    }
+    pub fn get_desc(&self) -> String {
+        if let Some(weapon) = self.get_weapon() {
+            weapon.get_desc()
+        } else if let Some(armor) = self.get_armor() {
+            armor.get_desc()
+        } else if let Item::Misc(MiscItem {
+            rots: Some((turns, _)),
+            ..
+        }) = self.item
+        {
+            format!("{}\nROTS IN: {}", self.item.get_desc(), turns - self.age)
+        } else {
+            self.item.get_desc()
+        }
+    }
+    /// Stats of the weapon with affixes applied
+    pub fn get_weapon(&self) -> Option<Weapon> {
+        let Item::Weapon(weapon) = self.item else {
+            return None;
+        };
+        let mut weapon = weapon.clone();
+        for affix in &self.affixes {
+            weapon.base_damage += affix.damage;
+            weapon.attack_range.end += affix.range;
+            weapon.on_hit = affix.on_hit.or(weapon.on_hit);
+            weapon.knockback += affix.knockback;
+        }
+        Some(weapon)
+    }
+    /// Stats of the armor with affixes applied
+    pub fn get_armor(&self) -> Option<Armor> {
+        let Item::Armor(armor) = self.item else {
+            return None;
+        };
+        let mut armor = armor.clone();
+        for affix in &self.affixes {
+            armor.block_chance = (armor.block_chance + affix.block_chance).min(0.9);
+        }
+        Some(armor)
+    }
+    /// The same item with its durability back to the maximum
+    fn repaired(mut self) -> Self {
+        self.durability = self.item.max_durability();
+        self
+    }
+    pub fn is_damaged(&self) -> bool {
+        self.durability != self.item.max_durability()
+    }
+    /// Remaining durability as a fraction of the maximum durability
+    pub fn durability_fraction(&self) -> Option<f32> {
+        Some(self.durability? as f32 / self.item.max_durability()? as f32)
+    }
+    /// Lowers durability by one. Returns whether the item broke.
+    pub fn wear(&mut self) -> bool {
+        if let Some(durability) = &mut self.durability {
+            *durability = durability.saturating_sub(1);
+            *durability == 0
+        } else {
+            false
+        }
+    }
+}
+impl From<Item> for ItemInstance {
+    fn from(item: Item) -> Self {
+        Self::new(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::items::*;
+
+    #[test]
+    fn test_salvage() {
+        assert!(salvage(Item::Misc(&STICK), 0.0).is_empty());
+        assert!(
+            salvage(Item::Weapon(&STONE_SPEAR), 0.0)
+                == vec![Item::Misc(&STONE), Item::Misc(&STICK)]
+        );
+        let salvaged = salvage(Item::Armor(&IRON_ARMOR), 1.0);
+        assert_eq!(salvaged.len(), 1);
+        assert!([Item::Misc(&IRON_ORE), Item::Armor(&TUNIC)].contains(&salvaged[0]));
+    }
+
+    #[test]
+    fn test_range_metric() {
+        // diagonally adjacent is only in melee reach when you could also step there
+        assert!(!DAGGER.in_range((2, 2), (3, 3), false));
+        assert!(DAGGER.in_range((2, 2), (3, 3), true));
+        assert!(DAGGER.in_range((2, 2), (2, 3), false));
+        // bows measure in a straight line either way
+        assert_eq!(SHORTBOW.distance((2, 2), (5, 5), false), 4);
+        assert_eq!(SHORTBOW.distance((2, 2), (5, 5), true), 4);
+    }
+
+    #[test]
+    fn test_affixes() {
+        let sword = ItemInstance::roll(Item::Weapon(&IRON_SWORD), Rarity::Rare);
+        assert_eq!(sword.affixes.len(), 2);
+        assert!(
+            sword
+                .affixes
+                .iter()
+                .all(|f| f.target == AffixTarget::Weapon)
+        );
+
+        let stick = ItemInstance::roll(Item::Misc(&STICK), Rarity::Legendary);
+        assert!(stick.affixes.is_empty() && stick.rarity == Rarity::Common);
+
+        let mut bow = ItemInstance::new(Item::Weapon(&SHORTBOW));
+        bow.affixes = vec![&SHARP, &FARSIGHTED];
+        let stats = bow.get_weapon().unwrap();
+        assert_eq!(stats.base_damage, SHORTBOW.base_damage + SHARP.damage);
+        assert_eq!(stats.attack_range, 2..5);
+        assert_eq!(bow.get_name(), "sharp farsighted shortbow");
+    }
+
+    #[test]
+    fn test_identification() {
+        let mut identification = Identification::default();
+        let potion = ItemInstance::new(Item::Potion(&HEALING_POTION));
+        assert!(!identification.is_identified(&potion.item));
+        assert!(identification.get_name(&potion).ends_with(" potion"));
+
+        identification.identify(potion.item);
+        assert_eq!(identification.get_name(&potion), "healing potion");
+        assert!(identification.is_identified(&Item::Misc(&STICK)));
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut sword = ItemInstance::new(Item::Weapon(&IRON_SWORD));
+        let ore = ItemInstance::new(Item::Misc(&IRON_ORE));
+        let inventory = vec![Some(sword.clone()), Some(ore.clone())];
+        assert!(get_combinable(&inventory, 0).is_empty());
+
+        sword.wear();
+        let inventory = vec![Some(sword.clone()), Some(ore.clone())];
+        assert_eq!(get_combinable(&inventory, 0), vec![1]);
+        let (repaired, _) = combine(ore.clone(), sword);
+        assert!(repaired.item == Item::Weapon(&IRON_SWORD) && !repaired.is_damaged());
+
+        // repairing keeps everything else about the item
+        let mut rare = ItemInstance::new(Item::Weapon(&IRON_SWORD));
+        rare.rarity = Rarity::Rare;
+        rare.affixes = vec![&SHARP];
+        rare.wear();
+        let (repaired, _) = combine(rare, ore);
+        assert!(!repaired.is_damaged() && repaired.rarity == Rarity::Rare);
+        assert_eq!(repaired.get_name(), "sharp iron sword");
+    }
+
+    #[test]
+    fn test_arrow_crafting() {
+        let (arrows, _) = combine(Item::Misc(&STICK).into(), Item::Misc(&BONE).into());
+        assert!(arrows.item == Item::Ammo(&BONE_ARROW));
+        assert_eq!(arrows.count, ARROWS_PER_CRAFT);
+
+        let mut stack = ItemInstance::new(Item::Ammo(&STONE_ARROW));
+        stack.count = 12;
+        let (poisoned, leftover) = combine(Item::Misc(&POISON_MUSHROOM).into(), stack);
+        assert!(poisoned.item == Item::Ammo(&POISON_ARROW));
+        assert_eq!(poisoned.get_name(), "poison arrow x5");
+        let leftover = leftover.unwrap();
+        assert!(leftover.item == Item::Ammo(&STONE_ARROW));
+        assert_eq!(leftover.count, 12 - ARROWS_PER_CRAFT);
+    }
+
+    #[test]
+    fn test_rotting() {
+        let mut flesh = ItemInstance::new(Item::Misc(&FLESH));
+        for _ in 1..FLESH_ROT_TURNS {
+            flesh.age();
         }
+        assert!(flesh.item == Item::Misc(&FLESH));
+        flesh.age();
+        assert!(flesh.item == Item::Misc(&ROTTEN_FLESH));
+        flesh.age();
+        assert_eq!(flesh.age, 0);
     }
 }
@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::events::{DamageSource, GameEvent, Target};
+
+/// Messages kept in the log before the oldest are dropped
+const MAX_MESSAGES: usize = 200;
+
+/// What a message is about, which decides its colour
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MessageKind {
+    /// Attacks made by the player
+    Combat,
+    /// Damage taken by the player
+    Hurt,
+    Item,
+    Crafting,
+    Status,
+    /// Level ups and floor transitions
+    Progress,
+}
+impl MessageKind {
+    pub fn color(self) -> Color {
+        match self {
+            MessageKind::Combat => WHITE,
+            MessageKind::Hurt => Color::from_hex(0xe43b44),
+            MessageKind::Item => Color::from_hex(0xfeae34),
+            MessageKind::Crafting => Color::from_hex(0x2ce8f5),
+            MessageKind::Status => Color::from_hex(0xb55088),
+            MessageKind::Progress => Color::from_hex(0x63c74d),
+        }
+    }
+}
+
+pub struct Message {
+    pub text: String,
+    pub kind: MessageKind,
+    /// Times the message was repeated in a row
+    pub count: u16,
+}
+impl Message {
+    pub fn display(&self) -> String {
+        if self.count > 1 {
+            format!("{} x{}", self.text, self.count)
+        } else {
+            self.text.clone()
+        }
+    }
+}
+
+/// Everything that happened over the run, newest last
+#[derive(Default)]
+pub struct MessageLog {
+    pub messages: VecDeque<Message>,
+}
+impl MessageLog {
+    /// Adds a message, folding it into the last one if it's the same
+    pub fn push(&mut self, kind: MessageKind, text: impl Into<String>) {
+        let text = text.into();
+        if let Some(last) = self.messages.back_mut()
+            && last.text == text
+            && last.kind == kind
+        {
+            last.count += 1;
+            return;
+        }
+        self.messages.push_back(Message {
+            text,
+            kind,
+            count: 1,
+        });
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+    }
+    /// Describes what happened in an event, if it's worth mentioning
+    pub fn on_event(&mut self, event: &GameEvent) {
+        let (kind, text) = match event {
+            GameEvent::DamageDealt {
+                target: Target::Player,
+                source,
+                amount,
+                ..
+            } => (
+                MessageKind::Hurt,
+                match source {
+                    DamageSource::Enemy(name) => format!("The {name} hits you for {amount}"),
+                    DamageSource::Poison => format!("You take {amount} poison damage"),
+                    DamageSource::Starvation => format!("You starve for {amount} damage"),
+                    DamageSource::Slam => format!("You're slammed for {amount} damage"),
+                    DamageSource::Player => format!("You take {amount} damage"),
+                },
+            ),
+            GameEvent::DamageDealt {
+                target: Target::Enemy(name),
+                source,
+                amount,
+                ..
+            } => (
+                MessageKind::Combat,
+                match source {
+                    DamageSource::Slam => format!("The {name} is slammed for {amount}"),
+                    DamageSource::Poison => format!("The {name} takes {amount} poison damage"),
+                    _ => format!("You hit the {name} for {amount}"),
+                },
+            ),
+            GameEvent::Dodged {
+                target: Target::Player,
+                blocked,
+                ..
+            } => (
+                MessageKind::Hurt,
+                if *blocked {
+                    String::from("Your armor blocks an attack")
+                } else {
+                    String::from("You dodge an attack")
+                },
+            ),
+            GameEvent::Dodged {
+                target: Target::Enemy(name),
+                ..
+            } => (
+                MessageKind::Combat,
+                format!("The {name} dodges your attack"),
+            ),
+            GameEvent::EnemyKilled { enemy, .. } => {
+                (MessageKind::Combat, format!("You kill the {}", enemy.name))
+            }
+            GameEvent::ItemPickedUp { name, dropped } => (
+                MessageKind::Item,
+                if *dropped {
+                    format!("No room for {name}, it falls to the floor")
+                } else {
+                    format!("You get {name}")
+                },
+            ),
+            GameEvent::ItemCrafted {
+                name,
+                ingredients: (a, b),
+            } => (
+                MessageKind::Crafting,
+                format!("You combine {a} and {b} into {name}"),
+            ),
+            GameEvent::ItemSalvaged { name } => {
+                (MessageKind::Crafting, format!("You salvage {name}"))
+            }
+            GameEvent::ItemThrown { name } => (MessageKind::Item, format!("You throw {name}")),
+            GameEvent::ItemBroke { name, .. } => (MessageKind::Item, format!("Your {name} breaks")),
+            GameEvent::StatusApplied { target, status } => (
+                MessageKind::Status,
+                match target {
+                    Target::Player => format!("You are {}", status.name()),
+                    Target::Enemy(name) => format!("The {name} is {}", status.name()),
+                },
+            ),
+            GameEvent::StatusExpired { target, status } => (
+                MessageKind::Status,
+                match target {
+                    Target::Player => format!("You are no longer {}", status.name()),
+                    Target::Enemy(name) => format!("The {name} is no longer {}", status.name()),
+                },
+            ),
+            GameEvent::LevelUp { level, .. } => {
+                (MessageKind::Progress, format!("You reach level {level}"))
+            }
+            GameEvent::FloorEntered { floor } => (
+                MessageKind::Progress,
+                format!("You descend to floor {}", floor + 1),
+            ),
+            GameEvent::PlayerDied { floor } => {
+                (MessageKind::Hurt, format!("You die on floor {}", floor + 1))
+            }
+            GameEvent::Won => (MessageKind::Progress, String::from("You clear the dungeon")),
+        };
+        self.push(kind, text);
+    }
+    /// The last `amount` messages, oldest first
+    pub fn recent(&self, amount: usize) -> impl Iterator<Item = &Message> {
+        self.messages
+            .iter()
+            .skip(self.messages.len().saturating_sub(amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::log::*;
+
+    #[test]
+    fn test_log() {
+        let mut log = MessageLog::default();
+        log.push(MessageKind::Combat, "Wait");
+        log.push(MessageKind::Combat, "Wait");
+        log.push(MessageKind::Item, "You pick up a stick");
+        let recent: Vec<String> = log.recent(3).map(|f| f.display()).collect();
+        assert_eq!(recent, vec!["Wait x2", "You pick up a stick"]);
+
+        for i in 0..MAX_MESSAGES {
+            log.push(MessageKind::Combat, format!("{i}"));
+        }
+        assert_eq!(log.messages.len(), MAX_MESSAGES);
+        assert_eq!(log.messages[0].text, "0");
+    }
+}
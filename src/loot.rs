@@ -26,6 +26,7 @@ pub static SKELETON_DROPS: LazyLock<LootTable> = LazyLock::new(|| {
             (0.5, LootEntry::Item(Item::Misc(&STICK))),
             (1.0, LootEntry::Item(Item::Misc(&BONE))),
             (0.1, LootEntry::Item(Item::Weapon(&SHORTBOW))),
+            (0.3, LootEntry::Item(Item::Ammo(&BONE_ARROW))),
             (0.5, LootEntry::None),
         ],
     }
@@ -43,6 +44,7 @@ pub static JUNK_LOOT: LazyLock<LootTable> = LazyLock::new(|| LootTable {
         (1.0, LootEntry::Item(Item::Misc(&STONE))),
         (2.0, LootEntry::Item(Item::Misc(&BONE))),
         (0.5, LootEntry::Item(Item::Weapon(&DAGGER))),
+        (0.5, LootEntry::Item(Item::Ammo(&STONE_ARROW))),
     ],
 });
 pub static SLIME_DROPS: LazyLock<LootTable> = LazyLock::new(|| LootTable {
@@ -58,6 +60,27 @@ pub static SPIDER_DROPS: LazyLock<LootTable> = LazyLock::new(|| LootTable {
         (1.0, LootEntry::None),
     ],
 });
+pub static CONSUMABLE_LOOT: LazyLock<LootTable> = LazyLock::new(|| LootTable {
+    entries: vec![
+        (3.0, LootEntry::Item(Item::Potion(&HEALING_POTION))),
+        (1.0, LootEntry::Item(Item::Potion(&GREATER_HEALING_POTION))),
+        (1.5, LootEntry::Item(Item::Potion(&POISON_POTION))),
+        (1.5, LootEntry::Item(Item::Potion(&CURE_POTION))),
+        (1.5, LootEntry::Item(Item::Potion(&REGENERATION_POTION))),
+        (2.0, LootEntry::Item(Item::Scroll(&IDENTIFY_SCROLL))),
+        (1.0, LootEntry::Item(Item::Scroll(&TELEPORT_SCROLL))),
+        (1.0, LootEntry::Item(Item::Scroll(&MAGIC_MAPPING_SCROLL))),
+        (1.0, LootEntry::Item(Item::Scroll(&FIREBALL_SCROLL))),
+        (1.0, LootEntry::Item(Item::Scroll(&FREEZE_SCROLL))),
+        (1.0, LootEntry::Item(Item::Scroll(&BLINK_SCROLL))),
+    ],
+});
+pub static WIZARD_DROPS: LazyLock<LootTable> = LazyLock::new(|| LootTable {
+    entries: vec![
+        (1.0, LootEntry::LootTable(&CONSUMABLE_LOOT)),
+        (1.0, LootEntry::None),
+    ],
+});
 
 enum LootEntry {
     None,
@@ -65,7 +88,7 @@ enum LootEntry {
     LootTable(&'static LootTable),
 }
 
-fn weighted_choice(choices: &[(f32, LootEntry)]) -> &LootEntry {
+pub fn weighted_choice<T>(choices: &[(f32, T)]) -> &T {
     let mut total = 0.0;
     for entry in choices {
         total += entry.0;
@@ -2,16 +2,32 @@ use macroquad::{miniquad::window::screen_size, prelude::*, rand};
 use utils::*;
 
 use crate::{
-    assets::Assets, dungeon::*, entities::*, items::StatusEffect, loot::LootTable,
+    assets::Assets,
+    classes::{CLASSES, Class},
+    dungeon::*,
+    entities::*,
+    events::GameEvent,
+    input::Action,
+    items::{StatusEffect, salvage},
+    loot::LootTable,
+    stats::{HighScores, ScoreEntry},
     ui::InventoryState,
 };
 
+mod abilities;
 mod assets;
+mod ballistics;
+mod classes;
 mod dungeon;
 mod entities;
+mod events;
+mod input;
 mod items;
+mod log;
 mod loot;
+mod morgue;
 mod particles;
+mod stats;
 mod ui;
 mod utils;
 
@@ -58,12 +74,26 @@ struct Dunfog<'a> {
     world_camera: Camera2D,
     state: GameState,
     inv_state: InventoryState,
+    /// Inventory slot picked with the keyboard
+    inv_selection: Option<usize>,
+    /// Open while changing key bindings
+    controls_screen: Option<ui::ControlsScreen>,
+    /// Open while reading the message log, holding how far it's scrolled back
+    message_log: Option<usize>,
+    /// Open while looking at the high scores
+    high_scores: Option<HighScores>,
     dead: Option<f32>,
     win: Option<f32>,
+    /// Whether the class selection screen is shown, before the run starts
+    choosing_class: bool,
+    /// Class card picked with the keyboard or gamepad
+    class_selection: Option<usize>,
+    /// Seed the run's rng was started with
+    seed: u64,
 }
 impl<'a> Dunfog<'a> {
-    fn new(assets: &'a Assets, dungeon: Dungeon) -> Self {
-        let mut player = Player::default();
+    fn new(assets: &'a Assets, dungeon: Dungeon, seed: u64) -> Self {
+        let mut player = Player::new(&CLASSES[0]);
         player.move_to(dungeon.player_spawn, &dungeon);
         player.center_camera((SCREEN_WIDTH, SCREEN_HEIGHT));
         let mut world_camera = create_camera(SCREEN_WIDTH, SCREEN_HEIGHT);
@@ -76,18 +106,81 @@ impl<'a> Dunfog<'a> {
             world_camera,
             state: GameState::Idle,
             inv_state: InventoryState::Closed,
+            inv_selection: None,
+            controls_screen: None,
+            message_log: None,
+            high_scores: None,
             dead: None,
             win: None,
+            choosing_class: true,
+            class_selection: None,
+            seed,
         }
     }
+    /// Replaces the player with a fresh one of the chosen class
+    fn start_run(&mut self, class: &'static Class, screen_size: (f32, f32)) {
+        self.player = Player::new(class);
+        self.player
+            .move_to(self.dungeon.player_spawn, &self.dungeon);
+        self.player.center_camera(screen_size);
+        self.choosing_class = false;
+    }
     fn die(&mut self) {
+        events::emit(GameEvent::PlayerDied { floor: self.floor });
+        self.end_run();
+    }
+    fn win(&mut self) {
+        events::emit(GameEvent::Won);
+        self.end_run();
+        self.win = Some(0.0);
+    }
+    fn end_run(&mut self) {
         self.dead = Some(0.0);
         self.inv_state = InventoryState::Closed;
         self.state = GameState::Idle;
     }
-    fn win(&mut self) {
-        self.die();
-        self.win = Some(0.0);
+    /// Adds the finished run to the high score file
+    fn record_score(&self) {
+        let mut scores = HighScores::load();
+        scores.add(ScoreEntry {
+            score: self.player.stats.score(self.player.level),
+            seed: self.seed,
+            date: stats::format_date(miniquad::date::now()),
+            class: self.player.class.name.to_string(),
+            level: self.player.level,
+            floor: self.floor + 1,
+            outcome: self.player.stats.outcome().to_string(),
+        });
+        scores.save();
+    }
+    fn remove_dead_enemies(&mut self) {
+        for enemy in self.dungeon.enemies.extract_if(.., |f| f.health <= 0.0) {
+            events::emit(GameEvent::EnemyKilled {
+                enemy: enemy.ty,
+                pos: (enemy.x, enemy.y),
+            });
+        }
+    }
+    /// Hands every queued event to the systems that react to them, including any events those
+    /// systems emit in turn
+    fn dispatch_events(&mut self) {
+        loop {
+            let queued = events::drain();
+            if queued.is_empty() {
+                break;
+            }
+            for event in queued {
+                particles::on_event(&event, &mut self.dungeon.screen_particles);
+                self.player.log.on_event(&event);
+                self.player.stats.on_event(&event);
+                self.player.on_event(&event);
+                self.dungeon.on_event(&event);
+                if let GameEvent::PlayerDied { .. } | GameEvent::Won = event {
+                    self.record_score();
+                    morgue::write(&self.player, &self.dungeon, self.floor, self.seed);
+                }
+            }
+        }
     }
     fn perform_enemy_actions(&mut self) {
         let mut buffer = Vec::new();
@@ -96,7 +189,7 @@ impl<'a> Dunfog<'a> {
         let mut enemy_positions: Vec<(usize, usize)> = buffer.iter().map(|f| (f.x, f.y)).collect();
         for enemy in buffer.iter_mut() {
             if enemy.awake {
-                let action = enemy.act(&mut self.dungeon, &mut self.player);
+                let action = enemy.act(&mut self.dungeon, &mut self.player, &enemy_positions);
                 if let EnemyAction::MoveTo(pos) = action {
                     if (self.player.x, self.player.y) == pos || enemy_positions.contains(&pos) {
                         enemy.current_action = Some(EnemyAction::Wait)
@@ -136,20 +229,47 @@ impl<'a> Dunfog<'a> {
                     self.dungeon.screen_particles.clear();
 
                     for (k, v) in self.player.status_effects.iter_mut() {
-                        if let StatusEffect::Poison = k {
-                            self.player.health -= 2.0;
-                            self.player.was_damaged = true;
+                        match k {
+                            StatusEffect::Poison => {
+                                self.player.health -= 2.0;
+                                self.player.was_damaged = true;
+                                events::emit(GameEvent::DamageDealt {
+                                    target: events::Target::Player,
+                                    source: events::DamageSource::Poison,
+                                    amount: 2.0,
+                                    pos: self.player.draw_pos - vec2(0.0, 4.0),
+                                });
+                            }
+                            StatusEffect::Frozen | StatusEffect::Regeneration => {}
                         }
 
                         *v -= 1;
                     }
-                    self.player.status_effects.retain(|_, v| *v > 0);
+                    let regen = self.player.regen_rate();
+                    self.player.heal(regen);
+                    for (status, _) in self.player.status_effects.extract_if(|_, v| *v == 0) {
+                        events::emit(GameEvent::StatusExpired {
+                            target: events::Target::Player,
+                            status,
+                        });
+                    }
+                    self.player.turns += 1;
+                    self.player.tick_cooldowns();
+                    self.player.tick_hunger(&mut self.dungeon);
+                    for item in self.player.inventory.iter_mut().flatten() {
+                        item.age();
+                    }
+                    for (_, _, item) in self.dungeon.items.iter_mut() {
+                        item.age();
+                    }
 
                     self.perform_enemy_actions();
                     if enemies_visible {
                         self.state = GameState::EnemyAction(ACTION_TIME);
                     } else {
                         self.state = GameState::Idle;
+                        // there's no enemy turn to show, so don't leave its particles lying around
+                        self.dispatch_events();
                         self.dungeon.particles.clear();
                         self.dungeon.screen_particles.clear();
                     }
@@ -172,10 +292,64 @@ impl<'a> Dunfog<'a> {
         }
     }
     fn update(&mut self) {
+        input::update_pad();
+        if let Some(scores) = &self.high_scores {
+            clear_background(BLACK);
+            if ui::draw_high_scores(scores, self.assets) {
+                self.high_scores = None;
+            }
+            return;
+        }
+        if input::is_pressed(Action::HighScores) {
+            self.high_scores = Some(HighScores::load());
+            return;
+        }
+        if self.choosing_class {
+            let (actual_screen_width, actual_screen_height) = screen_size();
+            let scale_factor =
+                (actual_screen_width / SCREEN_WIDTH).min(actual_screen_height / SCREEN_HEIGHT);
+            clear_background(BLACK);
+            if let Some(class) = ui::draw_class_select(&mut self.class_selection, self.assets) {
+                self.start_run(
+                    class,
+                    (
+                        actual_screen_width / scale_factor,
+                        actual_screen_height / scale_factor,
+                    ),
+                );
+            }
+            return;
+        }
+        if let Some(screen) = &mut self.controls_screen {
+            clear_background(BLACK);
+            if ui::draw_controls_screen(screen, self.assets) {
+                self.controls_screen = None;
+            }
+            return;
+        }
+        if input::is_pressed(Action::Controls) {
+            self.controls_screen = Some(ui::ControlsScreen::default());
+            return;
+        }
+        if let Some(scroll) = &mut self.message_log {
+            clear_background(BLACK);
+            if ui::draw_message_log(scroll, &self.player.log, self.assets) {
+                self.message_log = None;
+            }
+            return;
+        }
+        if input::is_pressed(Action::MessageLog) {
+            self.message_log = Some(0);
+            return;
+        }
         if self.player.health <= 0.0 && self.dead.is_none() {
             self.die();
         }
-        if self.dungeon.enemies.is_empty() && self.floor >= 4 && self.win.is_none() {
+        if self.dungeon.enemies.is_empty()
+            && self.floor >= 4
+            && self.win.is_none()
+            && self.dead.is_none()
+        {
             self.win();
         }
         let (actual_screen_width, actual_screen_height) = screen_size();
@@ -184,9 +358,14 @@ impl<'a> Dunfog<'a> {
         let (mouse_x, mouse_y) = mouse_position();
 
         let delta_time = get_frame_time();
+        if self.dead.is_none() {
+            self.player.stats.time_played += delta_time;
+        }
 
-        if (is_key_pressed(KeyCode::F) || is_key_pressed(KeyCode::Escape))
+        if (input::is_pressed(Action::Inventory) || input::is_pressed(Action::Cancel))
             && self.player.should_throw_item.is_none()
+            && self.player.should_read_scroll.is_none()
+            && self.player.target_cursor.is_none()
         {
             self.inv_state.toggle();
         }
@@ -198,6 +377,23 @@ impl<'a> Dunfog<'a> {
                 .items
                 .push((self.player.x, self.player.y, item));
         }
+        if let Some(index) = self.player.should_salvage_item {
+            self.player.should_salvage_item = None;
+            let mut item = self.player.inventory[index].take().unwrap();
+            if item.count > 1 {
+                // salvage one from the stack and leave the rest
+                let mut rest = item.clone();
+                rest.count -= 1;
+                item.count = 1;
+                self.player.inventory[index] = Some(rest);
+            }
+            events::emit(GameEvent::ItemSalvaged {
+                name: self.player.identification.get_name(&item),
+            });
+            for ingredient in salvage(item.item, SALVAGE_LOSS) {
+                self.player.give_item(ingredient.into(), &mut self.dungeon);
+            }
+        }
 
         let (mouse_x, mouse_y) = (mouse_x / scale_factor, mouse_y / scale_factor);
         let (mouse_tile_x, mouse_tile_y) = (
@@ -208,7 +404,7 @@ impl<'a> Dunfog<'a> {
         let mouse_delta = mouse_delta_position();
         let scroll = mouse_wheel();
 
-        if is_mouse_button_down(MouseButton::Middle) && self.dead.is_none() {
+        if input::is_down(Action::Pan) && self.dead.is_none() {
             self.player.camera_pos.x += mouse_delta.x as f32 * actual_screen_width
                 / scale_factor
                 / 2.
@@ -241,6 +437,18 @@ impl<'a> Dunfog<'a> {
                 old_mouse_world_y + SCREEN_HEIGHT / 2.0 - mouse_y / self.player.camera_zoom;
         }
 
+        if input::is_pressed(Action::Zoom) && self.dead.is_none() {
+            // cycle through zoom levels, keeping the middle of the screen in place
+            let zoom = ZOOM_LEVELS
+                .iter()
+                .copied()
+                .find(|f| *f > self.player.camera_zoom * SCROLL_AMT)
+                .unwrap_or(ZOOM_LEVELS[0]);
+            let centre = vec2(actual_screen_width, actual_screen_height) / scale_factor / 2.0;
+            self.player.camera_pos += centre / self.player.camera_zoom - centre / zoom;
+            self.player.camera_zoom = zoom;
+        }
+
         let cursor_tile = if mouse_tile_x >= 0.0
             && mouse_tile_y >= 0.0
             && mouse_tile_x < TILES_HORIZONTAL as f32
@@ -253,7 +461,7 @@ impl<'a> Dunfog<'a> {
 
         if let GameState::PlayerAction(_) = &self.state
             && let Some(PlayerAction::MoveDirection(dir)) = &self.player.active_action
-            && !is_mouse_button_down(MouseButton::Middle)
+            && !input::is_down(Action::Pan)
         {
             let max_dist = 16.0;
             let pos = self.player.draw_pos;
@@ -296,7 +504,10 @@ impl<'a> Dunfog<'a> {
             {
                 if let PlayerAction::GotoNextDungeon = action {
                     self.floor += 1;
+                    let eight_way = self.dungeon.eight_way;
                     self.dungeon = Dungeon::generate_dungeon(&DUNGEON_FLOORS[self.floor]);
+                    self.dungeon.eight_way = eight_way;
+                    events::emit(GameEvent::FloorEntered { floor: self.floor });
                     self.player.tile_status =
                         vec![TileStatus::Unknown; TILES_HORIZONTAL * TILES_VERTICAL];
                     self.player
@@ -307,13 +518,28 @@ impl<'a> Dunfog<'a> {
                     ));
                 } else {
                     self.player.active_action = Some(action);
-                    self.state = GameState::PlayerAction(ACTION_TIME);
+                    // resting skips straight through the turn animations
+                    let time = if self.player.resting.is_some() {
+                        0.0
+                    } else {
+                        ACTION_TIME
+                    };
+                    self.state = GameState::PlayerAction(time);
                 }
             }
+            if self.player.should_center_camera {
+                self.player.should_center_camera = false;
+                self.player.center_camera((
+                    actual_screen_width / scale_factor,
+                    actual_screen_height / scale_factor,
+                ));
+            }
             for enemy in self.dungeon.enemies.iter_mut() {
                 enemy.update(delta_time, &self.state);
             }
         }
+        self.remove_dead_enemies();
+        self.dispatch_events();
 
         set_camera(&self.world_camera);
         clear_background(BLACK);
@@ -352,16 +578,6 @@ impl<'a> Dunfog<'a> {
         }
 
         let time = get_time();
-        let dead = self.dungeon.enemies.extract_if(.., |f| f.health <= 0.0);
-        for enemy in dead {
-            self.player.enemies_slayed += 1;
-            if let Some(loot_table) = enemy.ty.death_drops
-                && let Some(item) = loot_table.get_item()
-            {
-                self.dungeon.items.push((enemy.x, enemy.y, *item));
-            }
-        }
-
         for enemy in self.dungeon.enemies.iter() {
             if let TileStatus::Known = self.player.tile_status[enemy.x + enemy.y * TILES_HORIZONTAL]
             {
@@ -370,12 +586,13 @@ impl<'a> Dunfog<'a> {
         }
         for (x, y, item) in self.dungeon.items.iter() {
             if let TileStatus::Known = self.player.tile_status[x + y * TILES_HORIZONTAL] {
-                let sprite = item.get_sprite();
-                self.assets.items.draw_tile(
+                let sprite = item.item.get_sprite();
+                self.assets.items.draw_tile_tinted(
                     (x * 8) as f32,
                     (y * 8) as f32,
                     sprite.x,
                     sprite.y,
+                    self.player.identification.tint(&item.item),
                     None,
                 );
             }
@@ -394,6 +611,25 @@ impl<'a> Dunfog<'a> {
                 .tileset
                 .draw_tile(mouse_tile_x * 8.0, mouse_tile_y * 8.0, 2.0, 0.0, None);
         }
+        let keyboard_cursor = match self.inv_state {
+            InventoryState::ThrowingItem(_, cursor) => cursor.map(|f| (f, true)),
+            _ => self.player.target_cursor.map(|f| (f, false)),
+        };
+        if let Some((tile, throwing)) = keyboard_cursor {
+            let color = if self.player.can_reach(tile, &self.dungeon, throwing) {
+                GREEN
+            } else {
+                RED
+            };
+            self.assets.tileset.draw_tile_tinted(
+                tile.0 as f32 * 8.0,
+                tile.1 as f32 * 8.0,
+                2.0,
+                0.0,
+                color,
+                None,
+            );
+        }
         set_default_camera();
         clear_background(BLACK);
 
@@ -424,27 +660,56 @@ impl<'a> Dunfog<'a> {
 
         ui::draw_ui(
             &mut self.inv_state,
+            &mut self.inv_selection,
             &mut self.player,
             self.assets,
             &self.dungeon,
         );
+        // inspect what's under the keyboard cursor, or else the mouse
+        let inspected = if let Some((tile, _)) = keyboard_cursor {
+            let zoom = scale_factor * self.player.camera_zoom;
+            Some((
+                tile,
+                (vec2(tile.0 as f32, tile.1 as f32) * 8.0 - self.player.camera_pos) * zoom,
+            ))
+        } else if matches!(self.inv_state, InventoryState::Closed) {
+            cursor_tile.map(|f| (f, Vec2::from(mouse_position())))
+        } else {
+            None
+        };
+        if self.dead.is_none()
+            && self.win.is_none()
+            && let Some((tile, pos)) = inspected
+            && let Some((title, lines)) = self.player.inspect(tile, &self.dungeon)
+        {
+            ui::draw_inspect_card(&title, &lines, self.assets, pos.x, pos.y);
+        }
         if let Some(win_time) = &mut self.win {
             *win_time += delta_time;
-            ui::draw_win_screen(*win_time, self.assets);
+            ui::draw_win_screen(*win_time, self.assets, &self.player);
         } else if let Some(dead_time) = &mut self.dead {
             *dead_time += delta_time;
             if ui::draw_dead_screen(*dead_time, self.assets, &self.player, self.floor) {
-                *self = Self::new(self.assets, Dungeon::generate_dungeon(&DUNGEON_FLOORS[0]))
+                let seed = reseed();
+                let mut dungeon = Dungeon::generate_dungeon(&DUNGEON_FLOORS[0]);
+                dungeon.eight_way = self.dungeon.eight_way;
+                *self = Self::new(self.assets, dungeon, seed)
             }
         }
     }
 }
 
+/// Seeds the rng from the clock, returning the seed
+fn reseed() -> u64 {
+    let seed = miniquad::date::now().to_bits();
+    rand::srand(seed);
+    seed
+}
+
 #[macroquad::main("dunfog")]
 async fn main() {
     let use_testing_dungeon = std::env::args().any(|f| f.as_str() == "test");
-    let seed = miniquad::date::now().to_bits();
-    rand::srand(seed);
+    let seed = reseed();
     println!("dunfog v{} - seed: {seed}", env!("CARGO_PKG_VERSION"));
     let assets = assets::Assets::default();
 
@@ -457,7 +722,7 @@ async fn main() {
         Dungeon::generate_dungeon(&DUNGEON_FLOORS[0])
     };
 
-    let mut dunfog = Dunfog::new(&assets, dungeon);
+    let mut dunfog = Dunfog::new(&assets, dungeon, seed);
     loop {
         dunfog.update();
         next_frame().await
@@ -0,0 +1,199 @@
+use std::fmt::Write;
+
+use crate::{
+    Tile,
+    dungeon::Dungeon,
+    entities::{Player, QUIVER_SLOT, TileStatus},
+    stats,
+    utils::*,
+};
+
+const MORGUE_DIR: &str = "morgue";
+/// Messages from the end of the log included in the file
+const LAST_MESSAGES: usize = 20;
+
+/// Writes a text file describing the finished run to the morgue directory.
+/// Fails silently where there's no file system (web).
+pub fn write(player: &Player, dungeon: &Dungeon, floor: usize, seed: u64) {
+    let date = stats::format_date(macroquad::miniquad::date::now());
+    let path = format!(
+        "{MORGUE_DIR}/{}-{date}-{seed}.txt",
+        player.class.name.to_lowercase()
+    );
+    let text = describe(player, dungeon, floor, seed, &date);
+    let _ = std::fs::create_dir_all(MORGUE_DIR).and_then(|_| std::fs::write(path, text));
+}
+
+/// The contents of a morgue file. `floor` counts from 0.
+pub fn describe(player: &Player, dungeon: &Dungeon, floor: usize, seed: u64, date: &str) -> String {
+    let mut text = String::new();
+    let stats = &player.stats;
+    let _ = writeln!(text, "dunfog v{} morgue file", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(text, "Seed: {seed}");
+    let _ = writeln!(text, "Date: {date}");
+
+    let _ = writeln!(text, "\n== Character ==");
+    let _ = writeln!(
+        text,
+        "{}, level {} ({}/{} xp)",
+        player.class.name,
+        player.level,
+        player.xp,
+        player.xp_to_next_level()
+    );
+    let _ = writeln!(text, "{} on floor {}", stats.outcome(), floor + 1);
+    let _ = writeln!(text, "Score: {}", stats.score(player.level));
+    let _ = writeln!(
+        text,
+        "Health: {}/{}",
+        player.health.max(0.0),
+        player.max_health()
+    );
+    let _ = writeln!(
+        text,
+        "Strength {}, Dexterity {}, Vitality {}",
+        player.attributes.strength, player.attributes.dexterity, player.attributes.vitality
+    );
+    let _ = writeln!(text, "Hunger: {}", player.hunger_state().name());
+
+    let _ = writeln!(text, "\n== Run ==");
+    for line in stats.summary(player.turns).iter().skip(1) {
+        let _ = writeln!(text, "{line}");
+    }
+
+    let item_name = |slot: usize| {
+        player.inventory[slot]
+            .as_ref()
+            .map_or(String::from("-"), |f| {
+                let name = player.identification.get_name(f);
+                match f.durability {
+                    Some(durability) => format!("{name} ({durability} durability)"),
+                    None => name,
+                }
+            })
+    };
+    let _ = writeln!(text, "\n== Equipment ==");
+    let _ = writeln!(text, "Weapon: {}", item_name(0));
+    let _ = writeln!(text, "Armor: {}", item_name(1));
+    let _ = writeln!(text, "Quiver: {}", item_name(QUIVER_SLOT));
+
+    let _ = writeln!(text, "\n== Inventory ==");
+    let carried: Vec<usize> = (2..QUIVER_SLOT)
+        .filter(|f| player.inventory[*f].is_some())
+        .collect();
+    if carried.is_empty() {
+        let _ = writeln!(text, "Nothing");
+    }
+    for slot in carried {
+        let _ = writeln!(text, "{}", item_name(slot));
+    }
+
+    let _ = writeln!(text, "\n== Status effects ==");
+    if player.status_effects.is_empty() {
+        let _ = writeln!(text, "None");
+    }
+    for (status, turns) in &player.status_effects {
+        let _ = writeln!(text, "{} ({turns} turns left)", status.name());
+    }
+
+    let _ = writeln!(text, "\n== Kills ==");
+    let mut kills: Vec<(&&str, &u32)> = stats.kills.iter().collect();
+    kills.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    if kills.is_empty() {
+        let _ = writeln!(text, "None");
+    }
+    for (name, count) in kills {
+        let _ = writeln!(text, "{count:>4} {name}");
+    }
+
+    let _ = writeln!(text, "\n== Floor {} ==", floor + 1);
+    text += &draw_map(player, dungeon);
+    let _ = writeln!(
+        text,
+        "@ you, # wall, . floor, > stairs, = chest, * ore, ! item, letters are enemies"
+    );
+
+    let _ = writeln!(text, "\n== Last messages ==");
+    for message in player.log.recent(LAST_MESSAGES) {
+        let _ = writeln!(text, "{}", message.display());
+    }
+    text
+}
+
+/// The explored part of the floor as ascii, one line per row
+fn draw_map(player: &Player, dungeon: &Dungeon) -> String {
+    let mut map = String::new();
+    for y in 0..TILES_VERTICAL {
+        let mut row = String::new();
+        for x in 0..TILES_HORIZONTAL {
+            let index = x + y * TILES_HORIZONTAL;
+            let known = matches!(player.tile_status[index], TileStatus::Known);
+            let glyph = if (x, y) == (player.x, player.y) {
+                '@'
+            } else if matches!(player.tile_status[index], TileStatus::Unknown) {
+                ' '
+            } else if let Some(enemy) = dungeon.enemies.iter().find(|f| (f.x, f.y) == (x, y))
+                && known
+            {
+                enemy.ty.name.chars().next().unwrap_or('?')
+            } else if dungeon.items.iter().any(|(ix, iy, _)| (*ix, *iy) == (x, y)) {
+                '!'
+            } else {
+                match dungeon.tiles[index] {
+                    Tile::Wall => '#',
+                    Tile::Floor | Tile::Path | Tile::Detail(..) => '.',
+                    Tile::Door => '>',
+                    Tile::Chest(..) => '=',
+                    Tile::Ore(..) => '*',
+                }
+            };
+            row.push(glyph);
+        }
+        map += row.trim_end();
+        map.push('\n');
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Tile,
+        classes::CLASSES,
+        dungeon::{DUNGEON_FLOORS, Dungeon},
+        entities::{BAT, Enemy, Player, TileStatus},
+        morgue::*,
+    };
+
+    #[test]
+    fn test_morgue() {
+        let mut tiles = vec![Tile::Wall; TILES_HORIZONTAL * TILES_VERTICAL];
+        for tile in tiles.iter_mut().take(4) {
+            *tile = Tile::Floor;
+        }
+        tiles[3] = Tile::Door;
+        let dungeon = Dungeon {
+            tiles,
+            player_spawn: (0, 0),
+            enemies: vec![Enemy::new(1, 0, &BAT), Enemy::new(2, 0, &BAT)],
+            particles: Vec::new(),
+            screen_particles: Vec::new(),
+            items: Vec::new(),
+            dungeon_floor: &DUNGEON_FLOORS[0],
+            eight_way: false,
+        };
+        let mut player = Player::new(&CLASSES[0]);
+        player.tile_status[1] = TileStatus::Known;
+        player.tile_status[2] = TileStatus::Remembered;
+        player.tile_status[3] = TileStatus::Remembered;
+        player.tile_status[TILES_HORIZONTAL] = TileStatus::Remembered;
+        *player.stats.kills.entry("bat").or_default() += 2;
+
+        let text = describe(&player, &dungeon, 1, 42, "2023-11-14");
+        assert!(text.contains("Seed: 42"));
+        assert!(text.contains("on floor 2"));
+        assert!(text.contains("   2 bat"));
+        // enemies on remembered tiles aren't drawn
+        assert!(text.contains("== Floor 2 ==\n@b.>\n#\n"));
+    }
+}
@@ -1,6 +1,9 @@
 use macroquad::prelude::*;
 
-use crate::assets::Assets;
+use crate::{
+    assets::Assets,
+    events::{GameEvent, Target},
+};
 
 pub trait Particle {
     fn draw(&mut self, time: f32, assets: &Assets);
@@ -31,6 +34,23 @@ impl Particle for ProjectileParticle {
         );
     }
 }
+/// Flashes a tile with a colour, fading out over the action
+pub struct TileFlashParticle {
+    pub x: usize,
+    pub y: usize,
+    pub color: Color,
+}
+impl Particle for TileFlashParticle {
+    fn draw(&mut self, time: f32, _assets: &Assets) {
+        draw_rectangle(
+            self.x as f32 * 8.0,
+            self.y as f32 * 8.0,
+            8.0,
+            8.0,
+            self.color.with_alpha(0.6 * (1.0 - time)),
+        );
+    }
+}
 pub struct TextParticle {
     pub text: String,
     pub color: Color,
@@ -53,3 +73,29 @@ impl ScreenParticle for TextParticle {
         );
     }
 }
+/// Floating text for damage, dodges, broken items and level ups
+pub fn on_event(event: &GameEvent, particles: &mut Vec<Box<dyn ScreenParticle>>) {
+    let (text, color, origin) = match event {
+        GameEvent::DamageDealt {
+            target: Target::Player,
+            amount,
+            pos,
+            ..
+        } => (format!("-{amount}"), RED, *pos),
+        GameEvent::DamageDealt { amount, pos, .. } => (format!("-{amount}"), GREEN, *pos),
+        GameEvent::Dodged {
+            target: Target::Player,
+            pos,
+            ..
+        } => (String::from("Dodged!"), WHITE, *pos),
+        GameEvent::Dodged { pos, .. } => (String::from("Dodged!"), RED, *pos),
+        GameEvent::ItemBroke { pos, .. } => (String::from("Broke!"), ORANGE, *pos),
+        GameEvent::LevelUp { pos, .. } => (String::from("Level up!"), GOLD, *pos),
+        _ => return,
+    };
+    particles.push(Box::new(TextParticle {
+        text,
+        color,
+        origin,
+    }));
+}
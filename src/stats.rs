@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use crate::events::{DamageSource, GameEvent, Target};
+
+const SCORES_PATH: &str = "scores.txt";
+/// Runs kept in the high score table
+const MAX_SCORES: usize = 10;
+
+/// Tallies for the current run, kept up to date from game events
+#[derive(Default)]
+pub struct RunStats {
+    /// Damage dealt to each type of enemy, by name
+    pub damage_dealt: HashMap<&'static str, f32>,
+    /// Damage taken from each source, see [`DamageSource::name`]
+    pub damage_taken: HashMap<&'static str, f32>,
+    /// Kills of each type of enemy, by name
+    pub kills: HashMap<&'static str, u32>,
+    pub items_crafted: u32,
+    pub items_thrown: u32,
+    pub floors_cleared: u32,
+    /// Seconds spent playing, not counting menus
+    pub time_played: f32,
+    /// Set once the run is over, see [`RunStats::outcome`]
+    pub cause_of_death: Option<String>,
+    pub won: bool,
+    last_hurt_by: Option<DamageSource>,
+}
+impl RunStats {
+    pub fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::DamageDealt {
+                target: Target::Enemy(name),
+                amount,
+                ..
+            } => *self.damage_dealt.entry(name).or_default() += amount,
+            GameEvent::DamageDealt {
+                target: Target::Player,
+                source,
+                amount,
+                ..
+            } => {
+                *self.damage_taken.entry(source.name()).or_default() += amount;
+                self.last_hurt_by = Some(*source);
+            }
+            GameEvent::EnemyKilled { enemy, .. } => *self.kills.entry(enemy.name).or_default() += 1,
+            GameEvent::ItemCrafted { .. } => self.items_crafted += 1,
+            GameEvent::ItemThrown { .. } => self.items_thrown += 1,
+            GameEvent::FloorEntered { .. } => self.floors_cleared += 1,
+            GameEvent::Won => {
+                self.floors_cleared += 1;
+                self.won = true;
+            }
+            GameEvent::PlayerDied { .. } => {
+                self.cause_of_death = Some(match self.last_hurt_by {
+                    Some(DamageSource::Enemy(name)) => {
+                        format!("Killed by {} {name}", article(name))
+                    }
+                    Some(DamageSource::Poison) => String::from("Died of poison"),
+                    Some(DamageSource::Starvation) => String::from("Starved to death"),
+                    _ => String::from("Died"),
+                })
+            }
+            _ => {}
+        }
+    }
+    /// How the run ended
+    pub fn outcome(&self) -> &str {
+        if self.won {
+            "Cleared the dungeon"
+        } else {
+            self.cause_of_death.as_deref().unwrap_or("Still alive")
+        }
+    }
+    pub fn score(&self, level: u32) -> u32 {
+        let kills: u32 = self.kills.values().sum();
+        let bonus = if self.won { 1000 } else { 0 };
+        self.floors_cleared * 100 + kills * 10 + level * 50 + bonus
+    }
+    /// Lines describing the run, for the end screens. `turns` comes from [`Player::turns`].
+    ///
+    /// [`Player::turns`]: crate::entities::Player::turns
+    pub fn summary(&self, turns: u32) -> Vec<String> {
+        let seconds = self.time_played as u32;
+        let mut lines = vec![
+            self.outcome().to_string(),
+            format!("Turns taken: {turns}"),
+            format!("Time played: {}:{:02}", seconds / 60, seconds % 60),
+            format!("Floors cleared: {}", self.floors_cleared),
+            format!("Items crafted: {}", self.items_crafted),
+            format!("Items thrown: {}", self.items_thrown),
+        ];
+        let mut names: Vec<&str> = self
+            .damage_dealt
+            .keys()
+            .chain(self.damage_taken.keys())
+            .copied()
+            .collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            lines.push(format!(
+                "{name}: {} dealt, {} taken",
+                self.damage_dealt.get(name).unwrap_or(&0.0),
+                self.damage_taken.get(name).unwrap_or(&0.0)
+            ));
+        }
+        lines
+    }
+}
+
+/// A finished run in the high score table
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScoreEntry {
+    pub score: u32,
+    pub seed: u64,
+    /// Day the run ended, as `YYYY-MM-DD`
+    pub date: String,
+    pub class: String,
+    pub level: u32,
+    /// Floor the run ended on, counting from 1
+    pub floor: usize,
+    pub outcome: String,
+}
+
+/// Past runs, best first
+#[derive(Default)]
+pub struct HighScores(pub Vec<ScoreEntry>);
+impl HighScores {
+    /// Reads scores from lines like `score;seed;date;class;level;floor;outcome`.
+    /// Lines that don't parse are skipped.
+    pub fn parse(file: &str) -> Self {
+        let mut scores = Self::default();
+        for line in file.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.splitn(7, ';').collect();
+            let [score, seed, date, class, level, floor, outcome] = fields[..] else {
+                continue;
+            };
+            let (Ok(score), Ok(seed), Ok(level), Ok(floor)) =
+                (score.parse(), seed.parse(), level.parse(), floor.parse())
+            else {
+                continue;
+            };
+            scores.add(ScoreEntry {
+                score,
+                seed,
+                date: date.to_string(),
+                class: class.to_string(),
+                level,
+                floor,
+                outcome: outcome.to_string(),
+            });
+        }
+        scores
+    }
+    pub fn serialize(&self) -> String {
+        let mut file = String::from("# score;seed;date;class;level;floor;outcome\n");
+        for f in &self.0 {
+            file += &format!(
+                "{};{};{};{};{};{};{}\n",
+                f.score, f.seed, f.date, f.class, f.level, f.floor, f.outcome
+            );
+        }
+        file
+    }
+    pub fn load() -> Self {
+        std::fs::read_to_string(SCORES_PATH)
+            .map(|f| Self::parse(&f))
+            .unwrap_or_default()
+    }
+    /// Writes the scores to the scores file. Fails silently where there's no file system (web).
+    pub fn save(&self) {
+        let _ = std::fs::write(SCORES_PATH, self.serialize());
+    }
+    /// Adds a run, dropping the worst one when the table is full
+    pub fn add(&mut self, entry: ScoreEntry) {
+        let index = self.0.partition_point(|f| f.score >= entry.score);
+        self.0.insert(index, entry);
+        self.0.truncate(MAX_SCORES);
+    }
+}
+
+/// "a" or "an", whichever goes before `name`
+fn article(name: &str) -> &'static str {
+    if name.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        "an"
+    } else {
+        "a"
+    }
+}
+
+/// Formats seconds since the unix epoch as a `YYYY-MM-DD` date
+pub fn format_date(timestamp: f64) -> String {
+    // days to civil date, from http://howardhinnant.github.io/date_algorithms.html
+    let days = (timestamp / 86400.0).floor() as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::stats::*;
+
+    #[test]
+    fn test_high_scores() {
+        let entry = |score| ScoreEntry {
+            score,
+            seed: 42,
+            date: format_date(1_700_000_000.0),
+            class: String::from("Warrior"),
+            level: 3,
+            floor: 2,
+            outcome: String::from("Killed by a bat; twice"),
+        };
+        assert_eq!(entry(0).date, "2023-11-14");
+
+        let mut scores = HighScores::default();
+        for score in [50, 300, 100] {
+            scores.add(entry(score));
+        }
+        let parsed = HighScores::parse(&scores.serialize());
+        assert_eq!(parsed.0, vec![entry(300), entry(100), entry(50)]);
+
+        for _ in 0..MAX_SCORES {
+            scores.add(entry(200));
+        }
+        assert_eq!(scores.0.len(), MAX_SCORES);
+        assert_eq!(scores.0[0], entry(300));
+        assert!(scores.0.iter().all(|f| f.score >= 200));
+        assert!(HighScores::parse("garbage\n1;2;3").0.is_empty());
+    }
+
+    #[test]
+    fn test_cause_of_death() {
+        for (name, cause) in [
+            ("bat", "Killed by a bat"),
+            ("archwizard", "Killed by an archwizard"),
+        ] {
+            let mut stats = RunStats::default();
+            stats.on_event(&GameEvent::DamageDealt {
+                target: Target::Player,
+                source: DamageSource::Enemy(name),
+                amount: 2.0,
+                pos: macroquad::math::Vec2::ZERO,
+            });
+            stats.on_event(&GameEvent::PlayerDied { floor: 0 });
+            assert_eq!(stats.outcome(), cause);
+        }
+    }
+}
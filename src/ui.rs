@@ -3,9 +3,16 @@ use macroquad::{miniquad::window::screen_size, prelude::*};
 use crate::{
     Tile,
     assets::Assets,
+    classes::{CLASSES, Class},
     dungeon::Dungeon,
-    entities::Player,
-    items::{Item, combine, get_combinable},
+    entities::{Attribute, HungerState, Player, QUIVER_SLOT},
+    events::{self, GameEvent},
+    input::{self, ABILITY_ACTIONS, ACTIONS, Action, Binding},
+    items::{
+        Identification, Item, ItemInstance, ScrollEffect, combine, get_combinable, get_ingredients,
+    },
+    log::MessageLog,
+    stats::HighScores,
     utils::*,
 };
 
@@ -14,22 +21,26 @@ const UI_BORDER: Color = Color::from_hex(0xc0cbdc);
 
 pub enum InventoryAction {
     None,
-    CtxMenuOpen(usize, f32, f32),
+    /// Slot the menu is for, where it's drawn, and the row picked with the keyboard or gamepad
+    CtxMenuOpen(usize, f32, f32, Option<usize>),
     MovingItem(usize),
     CombiningItem(usize, Vec<usize>),
+    /// Picking which of the listed slots to identify, after reading a scroll of identify
+    IdentifyingItem(Vec<usize>),
 }
 
 pub enum InventoryState {
     Closed,
     Inventory(InventoryAction),
-    ThrowingItem(usize),
+    /// Item being thrown (or scroll being aimed), and where the keyboard cursor is aiming it
+    ThrowingItem(usize, Option<(usize, usize)>),
 }
 impl InventoryState {
     pub fn toggle(&mut self) {
         *self = match self {
             InventoryState::Closed => InventoryState::Inventory(InventoryAction::None),
             InventoryState::Inventory(_) => InventoryState::Closed,
-            InventoryState::ThrowingItem(_) => InventoryState::Closed,
+            InventoryState::ThrowingItem(..) => InventoryState::Closed,
         }
     }
 }
@@ -65,17 +76,189 @@ pub fn draw_tooltip(text: &str, assets: &Assets) {
         },
     );
 }
-#[expect(dead_code)]
 pub fn ui_rect(x: f32, y: f32, w: f32, h: f32) {
     draw_rectangle(x, y, w, h, UI_BORDER);
     draw_rectangle(x + 1.0, y + 1.0, w - 2.0, h - 2.0, UI_BACKGROUND);
 }
+/// Draws the player's nutrition next to the healthbar, labelled with how hungry they are
+fn draw_hunger_meter(player: &Player, assets: &Assets, scale_factor: f32) {
+    let x = 99.0 * scale_factor;
+    let y = 2.0 * scale_factor;
+    ui_rect(x, y, 40.0 * scale_factor, 13.0 * scale_factor);
+    let state = player.hunger_state();
+    let color = match state {
+        HungerState::Satiated => Color::from_hex(0xfeae34),
+        HungerState::Hungry => Color::from_hex(0xf77622),
+        HungerState::Weak | HungerState::Starving => Color::from_hex(0xe43b44),
+    };
+    draw_rectangle(
+        x + 2.0 * scale_factor,
+        y + 2.0 * scale_factor,
+        36.0 * scale_factor,
+        9.0 * scale_factor,
+        BLACK,
+    );
+    draw_rectangle(
+        x + 2.0 * scale_factor,
+        y + 2.0 * scale_factor,
+        (36.0 * scale_factor * player.nutrition as f32 / MAX_NUTRITION as f32).floor(),
+        9.0 * scale_factor,
+        color,
+    );
+    draw_text_ex(
+        state.name(),
+        x + 3.0 * scale_factor,
+        y + 9.0 * scale_factor,
+        TextParams {
+            color: WHITE,
+            font: Some(&assets.font),
+            font_size: (scale_factor * 4.0) as u16,
+            ..Default::default()
+        },
+    );
+}
+/// Draws the player's abilities below the healthbar, with their hotkeys and cooldowns
+fn draw_ability_bar(player: &Player, assets: &Assets, scale_factor: f32) {
+    for (i, ability) in player.get_abilities().into_iter().enumerate() {
+        let x = (2.0 + i as f32 * 13.0) * scale_factor;
+        let y = 16.0 * scale_factor;
+        ui_rect(x, y, 12.0 * scale_factor, 12.0 * scale_factor);
+        if player.aiming_ability == Some(ability) {
+            draw_rectangle(
+                x + scale_factor,
+                y + scale_factor,
+                10.0 * scale_factor,
+                10.0 * scale_factor,
+                UI_BORDER,
+            );
+        }
+        assets.items.draw_tile(
+            x + 2.0 * scale_factor,
+            y + 2.0 * scale_factor,
+            ability.icon.0,
+            ability.icon.1,
+            Some(&DrawTextureParams {
+                dest_size: Some(vec2(8.0 * scale_factor, 8.0 * scale_factor)),
+                ..Default::default()
+            }),
+        );
+        let (text, color) = if let Some(cooldown) = player.cooldowns.get(ability.name) {
+            draw_rectangle(
+                x + scale_factor,
+                y + scale_factor,
+                10.0 * scale_factor,
+                10.0 * scale_factor,
+                Color::new(0.0, 0.0, 0.0, 0.6),
+            );
+            (cooldown.to_string(), GRAY)
+        } else {
+            (input::label(ABILITY_ACTIONS[i]), WHITE)
+        };
+        draw_text_ex(
+            &text,
+            x + 2.0 * scale_factor,
+            y + 11.0 * scale_factor,
+            TextParams {
+                color,
+                font: Some(&assets.font),
+                font_size: (scale_factor * 4.0) as u16,
+                ..Default::default()
+            },
+        );
+    }
+}
+/// Draws the panel with the player's level and stats next to the inventory,
+/// with buttons for spending attribute points
+fn draw_character_sheet(
+    player: &mut Player,
+    assets: &Assets,
+    inventory_x: f32,
+    inventory_y: f32,
+    scale_factor: f32,
+    (mouse_x, mouse_y): (f32, f32),
+    clicking: bool,
+) {
+    let x = inventory_x - 64.0 * scale_factor;
+    let y = inventory_y;
+    ui_rect(x, y, 62.0 * scale_factor, 78.0 * scale_factor);
+
+    let attributes = [
+        ("STR", Attribute::Strength),
+        ("DEX", Attribute::Dexterity),
+        ("VIT", Attribute::Vitality),
+    ];
+    let mut lines = vec![
+        format!(
+            "LVL {}  XP {}/{}",
+            player.level,
+            player.xp,
+            player.xp_to_next_level()
+        ),
+        format!("HP {}/{}", player.health.ceil(), player.max_health().ceil()),
+    ];
+    for (name, attribute) in attributes {
+        lines.push(format!("{name} {}", player.attributes.get(attribute)));
+    }
+    lines.push(format!("MELEE DMG +{}", player.melee_bonus()));
+    lines.push(format!("RANGED DMG +{}", player.ranged_bonus()));
+    lines.push(format!(
+        "DODGE {}%",
+        (player.dodge_chance() * 100.0).round()
+    ));
+    if player.attribute_points > 0 {
+        lines.push(format!("POINTS: {}", player.attribute_points));
+    }
+    for (i, line) in lines.iter().enumerate() {
+        draw_text_ex(
+            line,
+            x + 3.0 * scale_factor,
+            y + (8.0 + i as f32 * 8.0) * scale_factor,
+            TextParams {
+                color: WHITE,
+                font: Some(&assets.font),
+                font_size: (scale_factor * 6.0) as u16,
+                ..Default::default()
+            },
+        );
+    }
+    if player.attribute_points == 0 {
+        return;
+    }
+    for (i, (_, attribute)) in attributes.into_iter().enumerate() {
+        let button_x = x + 50.0 * scale_factor;
+        let button_y = y + (3.0 + (i + 2) as f32 * 8.0) * scale_factor;
+        let hovered = (button_x..button_x + 7.0 * scale_factor).contains(&mouse_x)
+            && (button_y..button_y + 7.0 * scale_factor).contains(&mouse_y);
+        draw_rectangle(
+            button_x,
+            button_y,
+            7.0 * scale_factor,
+            7.0 * scale_factor,
+            if hovered { UI_BORDER } else { UI_BACKGROUND },
+        );
+        draw_text_ex(
+            "+",
+            button_x + 2.0 * scale_factor,
+            button_y + 6.0 * scale_factor,
+            TextParams {
+                color: WHITE,
+                font: Some(&assets.font),
+                font_size: (scale_factor * 6.0) as u16,
+                ..Default::default()
+            },
+        );
+        if hovered && clicking {
+            player.raise_attribute(attribute);
+        }
+    }
+}
 fn slot_index_position(index: usize) -> (usize, usize) {
     let melee_slot = (32, 17);
     let inventory_start = (4, 45);
     match index {
         0 => melee_slot,
         1 => (melee_slot.0, melee_slot.1 + 13),
+        QUIVER_SLOT => (melee_slot.0 + 13, melee_slot.1),
         2..14 => {
             let index = index - 2;
             let x = index % 4;
@@ -87,8 +270,23 @@ fn slot_index_position(index: usize) -> (usize, usize) {
         }
     }
 }
+pub fn draw_durability_bar(x: f32, y: f32, w: f32, fraction: f32, scale_factor: f32) {
+    draw_rectangle(x, y, w * scale_factor, scale_factor, BLACK);
+    draw_rectangle(
+        x,
+        y,
+        (w * fraction).ceil() * scale_factor,
+        scale_factor,
+        if fraction > 0.3 {
+            Color::from_hex(0x63c74d)
+        } else {
+            RED
+        },
+    );
+}
 pub fn draw_item_hover_info(
-    item: &Item,
+    item: &ItemInstance,
+    identification: &Identification,
     assets: &Assets,
     mouse_x: f32,
     mouse_y: f32,
@@ -109,31 +307,52 @@ pub fn draw_item_hover_info(
             ..Default::default()
         },
     );
-    let sprite = item.get_sprite();
-    assets.items.draw_tile(
+    let sprite = item.item.get_sprite();
+    assets.items.draw_tile_tinted(
         x + 3.0 * scale_factor,
         y + 3.0 * scale_factor,
         sprite.x,
         sprite.y,
+        identification.tint(&item.item),
         Some(&DrawTextureParams {
             dest_size: Some(vec2(8.0 * scale_factor, 8.0 * scale_factor)),
             ..Default::default()
         }),
     );
-    let name = item.get_name().to_uppercase();
+    if let Some(fraction) = item.durability_fraction() {
+        draw_durability_bar(
+            x + 3.0 * scale_factor,
+            y + 12.0 * scale_factor,
+            8.0,
+            fraction,
+            scale_factor,
+        );
+    }
+    let mut name = identification.get_name(item).to_uppercase();
     let long_name = name.len() > 10;
-    draw_text_ex(
+    // names with affixes can get too long for one line, so split them at the most central space
+    if name.len() > 20
+        && let Some(split) = name
+            .match_indices(' ')
+            .map(|(i, _)| i)
+            .min_by_key(|i| i.abs_diff(name.len() / 2))
+    {
+        name.replace_range(split..split + 1, "\n");
+    }
+    draw_multiline_text_ex(
         &name,
         x + 13.0 * scale_factor,
         y + 10.0 * scale_factor - (if long_name { 4.0 * scale_factor } else { 0.0 }),
+        None,
         TextParams {
+            color: item.rarity.color(),
             font: Some(&assets.font),
             font_size: (scale_factor * (if long_name { 4.0 } else { 8.0 })) as u16,
             ..Default::default()
         },
     );
     draw_multiline_text_ex(
-        &item.get_desc(),
+        &identification.get_desc(item),
         x + 3.0 * scale_factor,
         y + 16.0 * scale_factor,
         None,
@@ -144,7 +363,384 @@ pub fn draw_item_hover_info(
         },
     );
 }
-pub fn draw_win_screen(mut win_time: f32, assets: &Assets) {
+/// Card describing an inspected tile, drawn up and to the left of (`x`, `y`)
+pub fn draw_inspect_card(title: &str, lines: &[String], assets: &Assets, x: f32, y: f32) {
+    let (actual_screen_width, actual_screen_height) = screen_size();
+    let scale_factor = (actual_screen_width / SCREEN_WIDTH)
+        .min(actual_screen_height / SCREEN_HEIGHT)
+        .floor()
+        .max(1.0);
+    let text_width = |text: &str, size: f32| {
+        measure_text(text, Some(&assets.font), (scale_factor * size) as u16, 1.0).width
+    };
+    let w = lines
+        .iter()
+        .map(|f| text_width(f, 4.0))
+        .fold(text_width(title, 6.0), f32::max)
+        .max(54.0 * scale_factor)
+        + 6.0 * scale_factor;
+    let h = (12.0 + lines.len() as f32 * 5.0) * scale_factor;
+    let x = (x - w + 2.0 * scale_factor).max(0.0);
+    let y = (y - h + 2.0 * scale_factor).max(0.0);
+    ui_rect(x, y, w, h);
+    draw_text_ex(
+        title,
+        x + 3.0 * scale_factor,
+        y + 9.0 * scale_factor,
+        TextParams {
+            color: WHITE,
+            font: Some(&assets.font),
+            font_size: (scale_factor * 6.0) as u16,
+            ..Default::default()
+        },
+    );
+    for (i, line) in lines.iter().enumerate() {
+        draw_text_ex(
+            line,
+            x + 3.0 * scale_factor,
+            y + (15.0 + i as f32 * 5.0) * scale_factor,
+            TextParams {
+                color: WHITE,
+                font: Some(&assets.font),
+                font_size: (scale_factor * 4.0) as u16,
+                ..Default::default()
+            },
+        );
+    }
+}
+/// Last few messages of the log, in the bottom left corner of the hud
+fn draw_recent_messages(log: &MessageLog, assets: &Assets, scale_factor: f32) {
+    const LINES: usize = 3;
+    let (_, actual_screen_height) = screen_size();
+    for (i, message) in log.recent(LINES).enumerate() {
+        let lines_below = log.messages.len().min(LINES) - i - 1;
+        draw_text_ex(
+            &message.display(),
+            2.0 * scale_factor,
+            actual_screen_height - (3.0 + lines_below as f32 * 5.0) * scale_factor,
+            TextParams {
+                color: message.kind.color(),
+                font: Some(&assets.font),
+                font_size: (scale_factor * 4.0) as u16,
+                ..Default::default()
+            },
+        );
+    }
+}
+/// Draws the whole message log, `scroll` messages back from the newest.
+/// Returns whether it should be closed.
+pub fn draw_message_log(scroll: &mut usize, log: &MessageLog, assets: &Assets) -> bool {
+    const ROWS: usize = 20;
+    const ROW_HEIGHT: f32 = 6.0;
+    let (actual_screen_width, actual_screen_height) = screen_size();
+    let scale_factor = (actual_screen_width / SCREEN_WIDTH)
+        .min(actual_screen_height / SCREEN_HEIGHT)
+        .floor()
+        .max(1.0);
+    let start_x = ((actual_screen_width - SCREEN_WIDTH * scale_factor) / 2.0).floor();
+    let start_y = ((actual_screen_height - SCREEN_HEIGHT * scale_factor) / 2.0).floor();
+    let text = |text: &str, x: f32, y: f32, color: Color| {
+        draw_text_ex(
+            text,
+            start_x + x * scale_factor,
+            start_y + y * scale_factor,
+            TextParams {
+                color,
+                font: Some(&assets.font),
+                font_size: (scale_factor * 6.0) as u16,
+                ..Default::default()
+            },
+        );
+    };
+
+    if input::is_pressed(Action::MessageLog) || input::is_pressed(Action::Cancel) {
+        return true;
+    }
+    let max_scroll = log.messages.len().saturating_sub(ROWS);
+    let wheel = mouse_wheel().1;
+    if input::is_pressed(Action::MoveUp) || wheel > 0.0 {
+        *scroll = (*scroll + 1).min(max_scroll);
+    } else if input::is_pressed(Action::MoveDown) || wheel < 0.0 {
+        *scroll = scroll.saturating_sub(1);
+    }
+
+    text("Messages", 4.0, 8.0, WHITE);
+    let end = log.messages.len() - *scroll;
+    let shown = log.messages.range(end.saturating_sub(ROWS)..end);
+    for (i, message) in shown.enumerate() {
+        text(
+            &message.display(),
+            4.0,
+            18.0 + i as f32 * ROW_HEIGHT,
+            message.kind.color(),
+        );
+    }
+    let footer = format!(
+        "{}/{}: scroll  {}: close",
+        input::label(Action::MoveUp),
+        input::label(Action::MoveDown),
+        input::label(Action::MessageLog)
+    );
+    text(&footer, 4.0, SCREEN_HEIGHT - 4.0, WHITE);
+    false
+}
+/// Draws the table of past runs. Returns whether it should be closed.
+pub fn draw_high_scores(scores: &HighScores, assets: &Assets) -> bool {
+    const ROW_HEIGHT: f32 = 6.0;
+    let (actual_screen_width, actual_screen_height) = screen_size();
+    let scale_factor = (actual_screen_width / SCREEN_WIDTH)
+        .min(actual_screen_height / SCREEN_HEIGHT)
+        .floor()
+        .max(1.0);
+    let start_x = ((actual_screen_width - SCREEN_WIDTH * scale_factor) / 2.0).floor();
+    let start_y = ((actual_screen_height - SCREEN_HEIGHT * scale_factor) / 2.0).floor();
+    let text = |text: &str, x: f32, y: f32, color: Color| {
+        draw_text_ex(
+            text,
+            start_x + x * scale_factor,
+            start_y + y * scale_factor,
+            TextParams {
+                color,
+                font: Some(&assets.font),
+                font_size: (scale_factor * 6.0) as u16,
+                ..Default::default()
+            },
+        );
+    };
+
+    if input::is_pressed(Action::HighScores) || input::is_pressed(Action::Cancel) {
+        return true;
+    }
+
+    text("High scores", 4.0, 8.0, WHITE);
+    if scores.0.is_empty() {
+        text("No runs finished yet", 4.0, 18.0, UI_BORDER);
+    }
+    for (i, entry) in scores.0.iter().enumerate() {
+        let y = 18.0 + i as f32 * ROW_HEIGHT * 2.0;
+        text(
+            &format!(
+                "{}. {} - {}, level {}, floor {}",
+                i + 1,
+                entry.score,
+                entry.class,
+                entry.level,
+                entry.floor
+            ),
+            4.0,
+            y,
+            if i == 0 { GOLD } else { WHITE },
+        );
+        text(
+            &format!("{} on {}, seed {}", entry.outcome, entry.date, entry.seed),
+            12.0,
+            y + ROW_HEIGHT,
+            UI_BORDER,
+        );
+    }
+    let footer = format!("{}: close", input::label(Action::HighScores));
+    text(&footer, 4.0, SCREEN_HEIGHT - 4.0, WHITE);
+    false
+}
+/// Selection on the controls screen
+#[derive(Default)]
+pub struct ControlsScreen {
+    selected: usize,
+    /// Waiting for a key or button to bind to the selected action
+    capturing: bool,
+}
+/// Draws the controls screen, where each action's bindings can be changed.
+/// Returns whether the screen was closed, saving the bindings.
+pub fn draw_controls_screen(screen: &mut ControlsScreen, assets: &Assets) -> bool {
+    const ROWS: usize = 20;
+    const ROW_HEIGHT: f32 = 6.0;
+    let (actual_screen_width, actual_screen_height) = screen_size();
+    let scale_factor = (actual_screen_width / SCREEN_WIDTH)
+        .min(actual_screen_height / SCREEN_HEIGHT)
+        .floor()
+        .max(1.0);
+    let (mouse_x, mouse_y) = mouse_position();
+    let start_x = ((actual_screen_width - SCREEN_WIDTH * scale_factor) / 2.0).floor();
+    let start_y = ((actual_screen_height - SCREEN_HEIGHT * scale_factor) / 2.0).floor();
+    let text = |text: &str, x: f32, y: f32, color: Color| {
+        draw_text_ex(
+            text,
+            start_x + x * scale_factor,
+            start_y + y * scale_factor,
+            TextParams {
+                color,
+                font: Some(&assets.font),
+                font_size: (scale_factor * 6.0) as u16,
+                ..Default::default()
+            },
+        );
+    };
+
+    // the keys used on this screen are fixed, so it can't lock itself out
+    if screen.capturing {
+        if let Some(binding) = Binding::get_pressed() {
+            input::bindings().toggle(ACTIONS[screen.selected].0, binding);
+            screen.capturing = false;
+        }
+    } else if is_key_pressed(KeyCode::Escape) {
+        input::bindings().save();
+        return true;
+    } else if is_key_pressed(KeyCode::Enter) {
+        screen.capturing = true;
+    } else if is_key_pressed(KeyCode::Backspace) || is_key_pressed(KeyCode::Delete) {
+        input::bindings().clear(ACTIONS[screen.selected].0);
+    } else if is_key_pressed(KeyCode::Up) {
+        screen.selected = screen.selected.saturating_sub(1);
+    } else if is_key_pressed(KeyCode::Down) {
+        screen.selected = (screen.selected + 1).min(ACTIONS.len() - 1);
+    } else if is_key_pressed(KeyCode::Left) {
+        screen.selected = screen.selected.saturating_sub(ROWS);
+    } else if is_key_pressed(KeyCode::Right) {
+        screen.selected = (screen.selected + ROWS).min(ACTIONS.len() - 1);
+    }
+
+    text("Controls", 4.0, 8.0, WHITE);
+    let bindings = input::bindings();
+    for (i, (action, name)) in ACTIONS.iter().enumerate() {
+        let x = 4.0 + (i / ROWS) as f32 * 128.0;
+        let y = 18.0 + (i % ROWS) as f32 * ROW_HEIGHT;
+        let hovered = (start_x + x * scale_factor..start_x + (x + 124.0) * scale_factor)
+            .contains(&mouse_x)
+            && (start_y + (y - ROW_HEIGHT + 1.0) * scale_factor
+                ..start_y + (y + 1.0) * scale_factor)
+                .contains(&mouse_y);
+        if hovered && is_mouse_button_pressed(MouseButton::Left) && !screen.capturing {
+            screen.selected = i;
+            screen.capturing = true;
+        }
+        let bound = if screen.capturing && screen.selected == i {
+            String::from("press a key...")
+        } else {
+            let labels: Vec<String> = bindings.get(*action).iter().map(|f| f.label()).collect();
+            labels.join(", ")
+        };
+        let color = if !bindings.conflicts(*action).is_empty() {
+            RED
+        } else if screen.selected == i || hovered {
+            GOLD
+        } else {
+            WHITE
+        };
+        text(name, x, y, color);
+        text(&bound, x + 62.0, y, color);
+    }
+    let conflicts = bindings.conflicts(ACTIONS[screen.selected].0);
+    let footer = if let Some(other) = conflicts.first() {
+        format!("Conflicts with {}", other.name())
+    } else {
+        String::from("Enter: add/remove key  Backspace: clear  Esc: save")
+    };
+    text(&footer, 4.0, SCREEN_HEIGHT - 4.0, WHITE);
+    false
+}
+/// Draws the class selection screen. Returns the class picked, if any.
+/// `selected` is the card picked with the keyboard or gamepad
+pub fn draw_class_select(selected: &mut Option<usize>, assets: &Assets) -> Option<&'static Class> {
+    let (actual_screen_width, actual_screen_height) = screen_size();
+    let scale_factor = (actual_screen_width / SCREEN_WIDTH)
+        .min(actual_screen_height / SCREEN_HEIGHT)
+        .floor()
+        .max(1.0);
+    let (mouse_x, mouse_y) = mouse_position();
+    let card_w = 70.0;
+    let card_h = 60.0;
+    let total_w = CLASSES.len() as f32 * (card_w + 4.0) - 4.0;
+    let start_x = ((actual_screen_width - total_w * scale_factor) / 2.0).floor();
+    let y = ((actual_screen_height - card_h * scale_factor) / 2.0).floor();
+
+    draw_text_ex(
+        "Choose your class",
+        start_x,
+        y - 8.0 * scale_factor,
+        TextParams {
+            color: WHITE,
+            font: Some(&assets.font),
+            font_size: (scale_factor * 8.0) as u16,
+            ..Default::default()
+        },
+    );
+    if let Some((dx, _)) = get_pressed_direction() {
+        *selected =
+            Some(selected.map_or(0, |f| f.saturating_add_signed(dx).min(CLASSES.len() - 1)));
+    }
+    let mut picked = None;
+    for (i, class) in CLASSES.iter().enumerate() {
+        let x = start_x + i as f32 * (card_w + 4.0) * scale_factor;
+        let hovered = (x..x + card_w * scale_factor).contains(&mouse_x)
+            && (y..y + card_h * scale_factor).contains(&mouse_y);
+        ui_rect(x, y, card_w * scale_factor, card_h * scale_factor);
+        if hovered || *selected == Some(i) {
+            draw_rectangle(
+                x + scale_factor,
+                y + scale_factor,
+                (card_w - 2.0) * scale_factor,
+                (card_h - 2.0) * scale_factor,
+                UI_BORDER,
+            );
+        }
+        draw_text_ex(
+            &format!("{}: {}", i + 1, class.name),
+            x + 3.0 * scale_factor,
+            y + 8.0 * scale_factor,
+            TextParams {
+                color: WHITE,
+                font: Some(&assets.font),
+                font_size: (scale_factor * 6.0) as u16,
+                ..Default::default()
+            },
+        );
+        for (j, (_, item, _)) in class.kit.iter().enumerate() {
+            let sprite = item.get_sprite();
+            assets.items.draw_tile_tinted(
+                x + (3.0 + j as f32 * 9.0) * scale_factor,
+                y + 12.0 * scale_factor,
+                sprite.x,
+                sprite.y,
+                item.tint(),
+                Some(&DrawTextureParams {
+                    dest_size: Some(vec2(8.0 * scale_factor, 8.0 * scale_factor)),
+                    ..Default::default()
+                }),
+            );
+        }
+        draw_multiline_text_ex(
+            class.desc,
+            x + 3.0 * scale_factor,
+            y + 29.0 * scale_factor,
+            None,
+            TextParams {
+                color: WHITE,
+                font: Some(&assets.font),
+                font_size: (scale_factor * 6.0) as u16,
+                ..Default::default()
+            },
+        );
+        if (hovered && is_mouse_button_pressed(MouseButton::Left))
+            || NUMBER_KEYS.get(i).is_some_and(|f| is_key_pressed(*f))
+            || (*selected == Some(i) && input::is_pressed(Action::Confirm))
+        {
+            picked = Some(class);
+        }
+    }
+    draw_text_ex(
+        &format!("{}: high scores", input::label(Action::HighScores)),
+        start_x,
+        y + (card_h + 10.0) * scale_factor,
+        TextParams {
+            color: WHITE,
+            font: Some(&assets.font),
+            font_size: (scale_factor * 6.0) as u16,
+            ..Default::default()
+        },
+    );
+    picked
+}
+pub fn draw_win_screen(mut win_time: f32, assets: &Assets, player: &Player) {
     let (actual_screen_width, actual_screen_height) = screen_size();
     let scale_factor = (actual_screen_width / SCREEN_WIDTH)
         .min(actual_screen_height / SCREEN_HEIGHT)
@@ -175,7 +771,7 @@ pub fn draw_win_screen(mut win_time: f32, assets: &Assets) {
             },
         );
         draw_text_ex(
-            &"Thanks for playing!",
+            "Thanks for playing!",
             x - 30.0 * scale_factor,
             (6.0 + 12.0) * scale_factor,
             TextParams {
@@ -185,8 +781,15 @@ pub fn draw_win_screen(mut win_time: f32, assets: &Assets) {
                 ..Default::default()
             },
         );
+        draw_inspect_card(
+            "Run stats",
+            &player.stats.summary(player.turns),
+            assets,
+            x - 4.0 * scale_factor,
+            y + assets.win_screen.height() * scale_factor,
+        );
         draw_multiline_text_ex(
-            &"VICTORY!\nYou won!",
+            "VICTORY!\nYou won!",
             x + 22.0 * scale_factor,
             y + 6.0 * scale_factor + 29.0 * scale_factor,
             None,
@@ -235,6 +838,13 @@ pub fn draw_dead_screen(
                 ..Default::default()
             },
         );
+        draw_inspect_card(
+            "Run stats",
+            &player.stats.summary(player.turns),
+            assets,
+            x - 4.0 * scale_factor,
+            y + assets.gravestone.height() * scale_factor,
+        );
         draw_multiline_text_ex(
             &format!(
                 "Died at floor: {floor}\nEnemies slayn: {}",
@@ -269,13 +879,15 @@ pub fn draw_dead_screen(
             },
         );
 
-        is_mouse_button_pressed(MouseButton::Left) && hovered
+        (is_mouse_button_pressed(MouseButton::Left) && hovered)
+            || input::is_pressed(Action::Confirm)
     } else {
         false
     }
 }
 pub fn draw_ui(
     state: &mut InventoryState,
+    selected: &mut Option<usize>,
     player: &mut Player,
     assets: &Assets,
     dungeon: &Dungeon,
@@ -299,7 +911,7 @@ pub fn draw_ui(
     draw_rectangle(
         (2.0 + 10.0) * scale_factor,
         (2.0 + 2.0) * scale_factor,
-        (84.0 * scale_factor * player.health / MAX_PLAYER_HP).floor(),
+        (84.0 * scale_factor * player.health / player.max_health()).floor(),
         9.0 * scale_factor,
         Color::from_hex(0x63c74d),
     );
@@ -318,6 +930,10 @@ pub fn draw_ui(
         },
     );
 
+    draw_hunger_meter(player, assets, scale_factor);
+    draw_recent_messages(&player.log, assets, scale_factor);
+    draw_ability_bar(player, assets, scale_factor);
+
     let clicking = is_mouse_button_pressed(MouseButton::Left);
 
     let x = (actual_screen_width - (assets.inventory.width() + 8.0) * scale_factor).floor();
@@ -338,40 +954,104 @@ pub fn draw_ui(
                         ..(slot_y as f32 * scale_factor + y + 12.0 * scale_factor))
                         .contains(&mouse_y)
             });
-            if clicking && let Some(i) = hovered_index {
+
+            // keyboard navigation, which uses the selected slot in place of the mouse
+            if clicking {
+                *selected = None;
+            }
+            if let Some(dir) = get_pressed_direction()
+                && !matches!(action, InventoryAction::CtxMenuOpen(..))
+            {
+                *selected = Some(selected.map_or(0, |f| neighbour_slot(f, dir)));
+            }
+            if input::is_pressed(Action::Target)
+                && let InventoryAction::CombiningItem(_, candidates)
+                | InventoryAction::IdentifyingItem(candidates) = &action
+                && !candidates.is_empty()
+            {
+                let next = selected
+                    .and_then(|f| candidates.iter().position(|c| *c == f))
+                    .map_or(0, |f| (f + 1) % candidates.len());
+                *selected = Some(candidates[next]);
+            }
+            let selected_pos = selected.map(|i| {
+                let (slot_x, slot_y) = slot_index_position(i);
+                (
+                    x + (slot_x as f32 + 6.0) * scale_factor,
+                    y + (slot_y as f32 + 6.0) * scale_factor,
+                )
+            });
+            let (activated, (pointer_x, pointer_y)) = if clicking {
+                (hovered_index, (mouse_x, mouse_y))
+            } else if input::is_pressed(Action::Confirm) {
+                (*selected, selected_pos.unwrap_or((mouse_x, mouse_y)))
+            } else {
+                (None, (mouse_x, mouse_y))
+            };
+            if let Some(i) = activated {
                 match &action {
-                    InventoryAction::MovingItem(index) => {
+                    InventoryAction::MovingItem(index)
+                        if i != *index
+                            && let Some(target) = &player.inventory[i]
+                            && target.stacks_with(player.inventory[*index].as_ref().unwrap()) =>
+                    {
+                        let moved = player.inventory[*index].take().unwrap();
+                        player.inventory[i].as_mut().unwrap().count += moved.count;
+                        action = &mut none_action;
+                        *state = InventoryState::Inventory(InventoryAction::None);
+                    }
+                    InventoryAction::MovingItem(index)
                         if item_can_go_in_slot(&player.inventory[i], *index)
-                            && item_can_go_in_slot(&player.inventory[*index], i)
-                        {
-                            (player.inventory[i], player.inventory[*index]) =
-                                (player.inventory[*index], player.inventory[i]);
-                            action = &mut none_action;
-                            *state = InventoryState::Inventory(InventoryAction::None);
-                        }
+                            && item_can_go_in_slot(&player.inventory[*index], i) =>
+                    {
+                        player.inventory.swap(i, *index);
+                        action = &mut none_action;
+                        *state = InventoryState::Inventory(InventoryAction::None);
                     }
-                    InventoryAction::CombiningItem(index, combinables) => {
-                        if combinables.contains(&i) {
-                            let new = combine(
-                                player.inventory[*index].take().unwrap(),
-                                player.inventory[i].take().unwrap(),
-                            );
+                    InventoryAction::CombiningItem(index, combinables)
+                        if combinables.contains(&i) =>
+                    {
+                        let (a, b) = (
+                            player.inventory[*index].take().unwrap(),
+                            player.inventory[i].take().unwrap(),
+                        );
+                        let ingredients = (
+                            player.identification.get_name(&a),
+                            player.identification.get_name(&b),
+                        );
+                        let a_is_ammo = matches!(a.item, Item::Ammo(_));
+                        let (new, leftover) = combine(a, b);
+                        events::emit(GameEvent::ItemCrafted {
+                            name: player.identification.get_name(&new),
+                            ingredients,
+                        });
+                        // unused ammo stays in its slot, and the new item takes the other one
+                        if let Some(leftover) = leftover {
+                            let (ammo_slot, other_slot) =
+                                if a_is_ammo { (*index, i) } else { (i, *index) };
+                            player.inventory[ammo_slot] = Some(leftover);
+                            player.inventory[other_slot] = Some(new);
+                        } else {
                             player.inventory[i] = Some(new);
-                            action = &mut none_action;
-                            *state = InventoryState::Inventory(InventoryAction::None);
                         }
+                        action = &mut none_action;
+                        *state = InventoryState::Inventory(InventoryAction::None);
                     }
-                    InventoryAction::None => {
-                        if player.inventory[i].is_some() {
-                            action = &mut none_action;
-                            *state = InventoryState::Inventory(InventoryAction::CtxMenuOpen(
-                                i,
-                                mouse_x - assets.ctx_menu.width() * scale_factor
-                                    + 2.0 * scale_factor,
-                                mouse_y - assets.ctx_menu.height() * scale_factor
-                                    + 2.0 * scale_factor,
-                            ));
-                        }
+                    InventoryAction::IdentifyingItem(candidates) if candidates.contains(&i) => {
+                        let item = player.inventory[i].as_ref().unwrap().item;
+                        player.identification.identify(item);
+                        action = &mut none_action;
+                        *state = InventoryState::Inventory(InventoryAction::None);
+                    }
+                    InventoryAction::None if player.inventory[i].is_some() => {
+                        action = &mut none_action;
+                        *state = InventoryState::Inventory(InventoryAction::CtxMenuOpen(
+                            i,
+                            pointer_x - assets.ctx_menu.width() * scale_factor + 2.0 * scale_factor,
+                            pointer_y - assets.ctx_menu.height() * scale_factor
+                                + 2.0 * scale_factor,
+                            (!clicking).then_some(0),
+                        ));
                     }
                     _ => {}
                 }
@@ -390,9 +1070,22 @@ pub fn draw_ui(
                     ..Default::default()
                 },
             );
+            draw_character_sheet(
+                player,
+                assets,
+                x,
+                y,
+                scale_factor,
+                (mouse_x, mouse_y),
+                clicking,
+            );
             // draw player portrait
             let player_portrait = vec2(13.0, 23.0) * scale_factor;
-            if let Some(Item::Weapon(item)) = &player.inventory[0] {
+            if let Some(ItemInstance {
+                item: Item::Weapon(item),
+                ..
+            }) = &player.inventory[0]
+            {
                 assets.items.draw_tile(
                     x + player_portrait.x - 4.0 * scale_factor * 2.0,
                     y + player_portrait.y - 2.0 * scale_factor * 2.0,
@@ -404,7 +1097,11 @@ pub fn draw_ui(
                     }),
                 );
             }
-            if let Some(Item::Armor(item)) = &player.inventory[1] {
+            if let Some(ItemInstance {
+                item: Item::Armor(item),
+                ..
+            }) = &player.inventory[1]
+            {
                 assets.items.draw_tile(
                     x + player_portrait.x,
                     y + player_portrait.y,
@@ -432,6 +1129,16 @@ pub fn draw_ui(
                         if !hovered { UI_BORDER } else { UI_BACKGROUND },
                     );
                 }
+                if *selected == Some(i) {
+                    draw_rectangle_lines(
+                        draw_x - 2.0 * scale_factor,
+                        draw_y - 2.0 * scale_factor,
+                        12.0 * scale_factor,
+                        12.0 * scale_factor,
+                        scale_factor,
+                        GOLD,
+                    );
+                }
                 if let InventoryAction::MovingItem(moving_index) = action
                     && i == *moving_index
                 {
@@ -443,21 +1150,51 @@ pub fn draw_ui(
                     continue;
                 }
                 if let Some(item) = slot {
-                    let sprite = item.get_sprite();
-                    assets.items.draw_tile(
+                    let sprite = item.item.get_sprite();
+                    assets.items.draw_tile_tinted(
                         draw_x,
                         draw_y,
                         sprite.x,
                         sprite.y,
+                        player.identification.tint(&item.item),
                         Some(&DrawTextureParams {
                             dest_size: Some(vec2(8.0 * scale_factor, 8.0 * scale_factor)),
                             ..Default::default()
                         }),
                     );
+                    if item.is_damaged()
+                        && let Some(fraction) = item.durability_fraction()
+                    {
+                        draw_durability_bar(
+                            draw_x,
+                            draw_y + 8.0 * scale_factor,
+                            8.0,
+                            fraction,
+                            scale_factor,
+                        );
+                    }
+                    if item.count > 1 {
+                        draw_text_ex(
+                            &item.count.to_string(),
+                            draw_x + 5.0 * scale_factor,
+                            draw_y + 10.0 * scale_factor,
+                            TextParams {
+                                color: WHITE,
+                                font: Some(&assets.font),
+                                font_size: (scale_factor * 4.0) as u16,
+                                ..Default::default()
+                            },
+                        );
+                    }
                 }
+                let candidates = match &action {
+                    InventoryAction::CombiningItem(_, candidates) => Some(candidates),
+                    InventoryAction::IdentifyingItem(candidates) => Some(candidates),
+                    _ => None,
+                };
                 if slot.is_some()
-                    && let InventoryAction::CombiningItem(_, combinables) = &action
-                    && !combinables.contains(&i)
+                    && let Some(candidates) = candidates
+                    && !candidates.contains(&i)
                 {
                     draw_rectangle(
                         draw_x - 2.0 * scale_factor,
@@ -475,13 +1212,15 @@ pub fn draw_ui(
                 _ => None,
             };
             if let Some(cursor_item) = cursor_item {
-                let item = &player.inventory[*cursor_item].unwrap();
-                let sprite = item.get_sprite();
-                assets.items.draw_tile(
-                    mouse_x - 4.0 * scale_factor,
-                    mouse_y - 4.0 * scale_factor,
+                let item = player.inventory[*cursor_item].as_ref().unwrap();
+                let sprite = item.item.get_sprite();
+                let (cursor_x, cursor_y) = selected_pos.unwrap_or((mouse_x, mouse_y));
+                assets.items.draw_tile_tinted(
+                    cursor_x - 4.0 * scale_factor,
+                    cursor_y - 4.0 * scale_factor,
                     sprite.x,
                     sprite.y,
+                    player.identification.tint(&item.item),
                     Some(&DrawTextureParams {
                         dest_size: Some(vec2(8.0 * scale_factor, 8.0 * scale_factor)),
                         ..Default::default()
@@ -489,25 +1228,26 @@ pub fn draw_ui(
                 );
             }
 
-            if let Some(hover) = hovered_index
+            let hover = match (hovered_index, selected_pos) {
+                (Some(hover), _) => Some((hover, (mouse_x, mouse_y))),
+                (None, Some(pos)) => selected.map(|f| (f, pos)),
+                _ => None,
+            };
+            if let Some((hover, (hover_x, hover_y))) = hover
                 && let Some(item) = &player.inventory[hover]
                 && let InventoryAction::None = action
             {
-                draw_item_hover_info(item, assets, mouse_x, mouse_y, scale_factor);
+                draw_item_hover_info(
+                    item,
+                    &player.identification,
+                    assets,
+                    hover_x,
+                    hover_y,
+                    scale_factor,
+                );
             }
-            if let InventoryAction::CtxMenuOpen(item_index, mx, my) = action {
+            if let InventoryAction::CtxMenuOpen(item_index, mx, my, row) = action {
                 let w = assets.ctx_menu.width() * scale_factor;
-                let h = assets.ctx_menu.height() * scale_factor;
-                draw_texture_ex(
-                    &assets.ctx_menu,
-                    *mx,
-                    *my,
-                    WHITE,
-                    DrawTextureParams {
-                        dest_size: Some(vec2(w, h)),
-                        ..Default::default()
-                    },
-                );
                 let player_free_slot = player.get_free_slot();
                 let player_first_free = player.inventory[0].is_none();
                 let player_second_free = player.inventory[1].is_none();
@@ -516,7 +1256,7 @@ pub fn draw_ui(
                 let combinable = get_combinable(&player.inventory, item_index);
                 let has_combinable = !combinable.is_empty();
 
-                let mut buttons: [CtxMenuButton; 5] = [
+                let mut buttons: [CtxMenuButton; 6] = [
                     ("Move", &|_| true, &|state, _| {
                         *state = InventoryState::Inventory(InventoryAction::MovingItem(item_index))
                     }),
@@ -537,25 +1277,23 @@ pub fn draw_ui(
                                     player_second_free
                                 }
                             }
+                            Item::Ammo(_) => {
+                                item_index != QUIVER_SLOT || player_free_slot.is_some()
+                            }
                             _ => false,
                         },
                         &|_, player| {
-                            let target_index = match player.inventory[item_index].unwrap() {
-                                Item::Weapon(_) => 0,
-                                Item::Armor(_) => 1,
-                                _ => panic!(),
-                            };
+                            let target_index =
+                                match player.inventory[item_index].as_ref().unwrap().item {
+                                    Item::Weapon(_) => 0,
+                                    Item::Armor(_) => 1,
+                                    Item::Ammo(_) => QUIVER_SLOT,
+                                    _ => panic!(),
+                                };
                             if item_index == target_index {
-                                (
-                                    player.inventory[item_index],
-                                    player.inventory[player_free_slot.unwrap()],
-                                ) = (
-                                    player.inventory[player_free_slot.unwrap()],
-                                    player.inventory[item_index],
-                                );
+                                player.inventory.swap(item_index, player_free_slot.unwrap());
                             } else {
-                                (player.inventory[item_index], player.inventory[target_index]) =
-                                    (player.inventory[target_index], player.inventory[item_index]);
+                                player.inventory.swap(item_index, target_index);
                             }
                         },
                     ),
@@ -567,8 +1305,15 @@ pub fn draw_ui(
                         ));
                     }),
                     ("Throw", &|item| item.throwable().is_some(), &|state, _| {
-                        *state = InventoryState::ThrowingItem(item_index)
+                        *state = InventoryState::ThrowingItem(item_index, None)
                     }),
+                    (
+                        "Salvage",
+                        &|item| get_ingredients(*item).is_some(),
+                        &|_, player| {
+                            player.should_salvage_item = Some(item_index);
+                        },
+                    ),
                     ("Drop", &|_| true, &|_, player| {
                         player.should_drop_item = Some(item_index);
                     }),
@@ -576,11 +1321,50 @@ pub fn draw_ui(
                 let consume_button: CtxMenuButton = ("Consume", &|_| true, &|_, player| {
                     player.consume(item_index);
                 });
-                if let Item::Misc(item) = &player.inventory[item_index].unwrap()
-                    && item.consumable.is_some()
-                {
-                    buttons[1] = consume_button;
+                let read_button: CtxMenuButton = ("Read", &|_| true, &|state, player| {
+                    let item = player.inventory[item_index].as_ref().unwrap().item;
+                    let Item::Scroll(scroll) = item else {
+                        panic!("invalid scroll");
+                    };
+                    // reading reveals what the scroll is before anything depends on its effect,
+                    // so an unknown aimed scroll only asks for a target once it's been read
+                    player.identification.identify(item);
+                    if scroll.effect == ScrollEffect::Identify {
+                        player.read_scroll(item_index);
+                        let candidates = player.get_unidentified();
+                        if !candidates.is_empty() {
+                            *state = InventoryState::Inventory(InventoryAction::IdentifyingItem(
+                                candidates,
+                            ));
+                        }
+                    } else if scroll.effect.is_aimed() {
+                        *state = InventoryState::ThrowingItem(item_index, None);
+                    } else {
+                        player.should_read_scroll = Some((item_index, None));
+                        *state = InventoryState::Closed;
+                    }
+                });
+                match &player.inventory[item_index].as_ref().unwrap().item {
+                    Item::Misc(item) if item.consumable.is_some() => {
+                        buttons[1] = consume_button;
+                    }
+                    Item::Potion(_) => buttons[1] = consume_button,
+                    Item::Scroll(_) => buttons[1] = read_button,
+                    _ => {}
+                }
+                draw_ctx_menu_background(assets, *mx, *my, buttons.len(), scale_factor);
+                let dy = if input::is_pressed(Action::MenuUp) {
+                    Some(-1)
+                } else if input::is_pressed(Action::MenuDown) {
+                    Some(1)
+                } else {
+                    None
+                };
+                if let Some(dy) = dy {
+                    *row =
+                        Some(row.map_or(0, |f| f.saturating_add_signed(dy).min(buttons.len() - 1)));
                 }
+                let confirmed_row = row.filter(|_| input::is_pressed(Action::Confirm));
                 let mut any_clicked = false;
                 for (index, (mut text, cond, on_click)) in buttons.into_iter().enumerate() {
                     let x = *mx + 2.0 * scale_factor;
@@ -588,14 +1372,22 @@ pub fn draw_ui(
                     let hovered = (x..(x + w)).contains(&mouse_x)
                         && (y..(y + 7.0 * scale_factor)).contains(&mouse_y);
 
-                    let mut color = if hovered { GOLD } else { WHITE };
-                    let disabled = !cond(&player.inventory[item_index].unwrap());
+                    let mut color = if hovered || *row == Some(index) {
+                        GOLD
+                    } else {
+                        WHITE
+                    };
+                    let disabled = !cond(&player.inventory[item_index].as_ref().unwrap().item);
                     if disabled {
                         color = GRAY;
                     }
-                    if !disabled && text == "Equip" && item_index <= 1 {
+                    if !disabled
+                        && text == "Equip"
+                        && (item_index <= 1 || item_index == QUIVER_SLOT)
+                    {
                         text = "Unequip";
                     }
+                    let shortcut = ctx_menu_shortcut(text);
 
                     draw_text_ex(
                         text,
@@ -608,7 +1400,11 @@ pub fn draw_ui(
                             ..Default::default()
                         },
                     );
-                    if !disabled && hovered && clicking {
+                    if !disabled
+                        && ((hovered && clicking)
+                            || confirmed_row == Some(index)
+                            || shortcut.is_some_and(input::is_pressed))
+                    {
                         any_clicked = true;
                         *state = InventoryState::Inventory(InventoryAction::None);
                         on_click(state, player);
@@ -620,42 +1416,73 @@ pub fn draw_ui(
                 }
             }
         }
-        InventoryState::ThrowingItem(index) => {
-            let item = &player.inventory[*index].unwrap();
-            let sprite = item.get_sprite();
-            assets.items.draw_tile(
+        InventoryState::ThrowingItem(index, cursor) => {
+            *cursor = player.step_target_cursor(*cursor, dungeon);
+            let item = player.inventory[*index].as_ref().unwrap();
+            let sprite = item.item.get_sprite();
+            assets.items.draw_tile_tinted(
                 mouse_x - 4.0 * scale_factor,
                 mouse_y - 4.0 * scale_factor,
                 sprite.x,
                 sprite.y,
+                player.identification.tint(&item.item),
                 Some(&DrawTextureParams {
                     dest_size: Some(vec2(8.0 * scale_factor, 8.0 * scale_factor)),
                     ..Default::default()
                 }),
             );
-            if clicking {
+            let target = if clicking {
                 let scale_factor =
                     (actual_screen_width / SCREEN_WIDTH).min(actual_screen_height / SCREEN_HEIGHT);
                 let (mouse_x, mouse_y) = (mouse_x / scale_factor, mouse_y / scale_factor);
-                let mouse_tile = vec2(
+                Some(vec2(
                     (((mouse_x) / player.camera_zoom + player.camera_pos.x) / 8.0).floor(),
                     (((mouse_y) / player.camera_zoom + player.camera_pos.y) / 8.0).floor(),
-                );
-
-                player.should_throw_item = Some((*index, mouse_tile));
+                ))
+            } else if input::is_pressed(Action::Confirm)
+                && let Some((x, y)) = cursor
+            {
+                Some(vec2(*x as f32, *y as f32))
+            } else {
+                None
+            };
+            if let Some(target) = target {
+                if let Item::Scroll(_) = item.item {
+                    player.should_read_scroll = Some((*index, Some(target)));
+                } else {
+                    player.should_throw_item = Some((*index, target));
+                }
                 *state = InventoryState::Closed;
             }
         }
+        _ if player.aiming_ability.is_some() => {
+            draw_tooltip("Pick a direction", assets);
+        }
+        _ if player.target_cursor.is_some() => {
+            draw_tooltip(
+                &format!("{}: confirm", input::label(Action::Confirm)),
+                assets,
+            );
+        }
         _ => match &dungeon.tiles[player.x + player.y * TILES_HORIZONTAL] {
             Tile::Chest(_, _, _) => {
-                draw_tooltip("E: interact", assets);
+                draw_tooltip(
+                    &format!("{}: interact", input::label(Action::Interact)),
+                    assets,
+                );
             }
             Tile::Door => {
-                draw_tooltip("E: descend", assets);
+                draw_tooltip(
+                    &format!("{}: descend", input::label(Action::Interact)),
+                    assets,
+                );
             }
             Tile::Ore(_, _, _) => {
                 if player.has_pickaxe() {
-                    draw_tooltip("E: Mine ore", assets);
+                    draw_tooltip(
+                        &format!("{}: Mine ore", input::label(Action::Interact)),
+                        assets,
+                    );
                 } else {
                     draw_tooltip("Pickaxe required", assets);
                 }
@@ -666,7 +1493,10 @@ pub fn draw_ui(
                     .iter()
                     .any(|(x, y, _)| *x == player.x && *y == player.y)
                 {
-                    draw_tooltip("E: pick up", assets);
+                    draw_tooltip(
+                        &format!("{}: pick up", input::label(Action::Interact)),
+                        assets,
+                    );
                 }
             }
         },
@@ -699,12 +1529,76 @@ pub fn draw_ui(
     );
 }
 
-fn item_can_go_in_slot(item: &Option<Item>, slot: usize) -> bool {
+/// Action which picks the context menu button with the given label
+fn ctx_menu_shortcut(text: &str) -> Option<Action> {
+    Some(match text {
+        "Move" => Action::MenuMove,
+        "Consume" => Action::MenuConsume,
+        "Read" => Action::MenuRead,
+        "Equip" | "Unequip" => Action::MenuEquip,
+        "Combine" => Action::MenuCombine,
+        "Throw" => Action::MenuThrow,
+        "Salvage" => Action::MenuSalvage,
+        "Drop" => Action::MenuDrop,
+        _ => return None,
+    })
+}
+/// The closest slot from `index` in the given direction, for moving the keyboard selection.
+/// Stays put if there's no slot that way.
+fn neighbour_slot(index: usize, dir: (isize, isize)) -> usize {
+    let (x, y) = slot_index_position(index);
+    (0..=QUIVER_SLOT)
+        .filter_map(|i| {
+            let (slot_x, slot_y) = slot_index_position(i);
+            let (dx, dy) = (slot_x as isize - x as isize, slot_y as isize - y as isize);
+            let along = dx * dir.0 + dy * dir.1;
+            let across = (dx * dir.1 - dy * dir.0).abs();
+            (along > 0).then_some((along + across * 2, i))
+        })
+        .min()
+        .map_or(index, |(_, i)| i)
+}
+/// Draws the context menu texture stretched to fit `rows` buttons,
+/// by repeating the texture's first row for every button.
+fn draw_ctx_menu_background(assets: &Assets, x: f32, y: f32, rows: usize, scale_factor: f32) {
+    const BORDER_TOP: f32 = 2.0;
+    const ROW_HEIGHT: f32 = 7.0;
+    let w = assets.ctx_menu.width();
+    let h = assets.ctx_menu.height();
+    let texture_rows = ((h - BORDER_TOP) / ROW_HEIGHT).floor();
+    let border_bottom = h - BORDER_TOP - texture_rows * ROW_HEIGHT;
+
+    let draw_part = |source_y: f32, source_h: f32, dest_y: f32| {
+        draw_texture_ex(
+            &assets.ctx_menu,
+            x,
+            y + dest_y * scale_factor,
+            WHITE,
+            DrawTextureParams {
+                source: Some(Rect::new(0.0, source_y, w, source_h)),
+                dest_size: Some(vec2(w * scale_factor, source_h * scale_factor)),
+                ..Default::default()
+            },
+        );
+    };
+    draw_part(0.0, BORDER_TOP, 0.0);
+    for row in 0..rows {
+        draw_part(BORDER_TOP, ROW_HEIGHT, BORDER_TOP + row as f32 * ROW_HEIGHT);
+    }
+    draw_part(
+        h - border_bottom,
+        border_bottom,
+        BORDER_TOP + rows as f32 * ROW_HEIGHT,
+    );
+}
+
+fn item_can_go_in_slot(item: &Option<ItemInstance>, slot: usize) -> bool {
     match item {
-        Some(item) => match item {
-            Item::Armor(_) => slot >= 1,
-            Item::Weapon(_) => slot == 0 || slot > 1,
-            Item::Misc(_) => slot > 1,
+        Some(item) => match item.item {
+            Item::Armor(_) => slot >= 1 && slot != QUIVER_SLOT,
+            Item::Weapon(_) => slot == 0 || (slot > 1 && slot != QUIVER_SLOT),
+            Item::Ammo(_) => slot > 1,
+            _ => slot > 1 && slot != QUIVER_SLOT,
         },
         None => true,
     }
@@ -715,3 +1609,18 @@ type CtxMenuButton<'a> = (
     &'a dyn Fn(&Item) -> bool,
     &'a dyn Fn(&mut InventoryState, &mut Player),
 );
+
+#[cfg(test)]
+mod tests {
+    use crate::{entities::QUIVER_SLOT, items::*, ui::*};
+
+    #[test]
+    fn test_item_slots() {
+        let armor = Some(ItemInstance::new(Item::Armor(&TUNIC)));
+        assert!(item_can_go_in_slot(&armor, 1) && item_can_go_in_slot(&armor, 2));
+        assert!(!item_can_go_in_slot(&armor, 0));
+        assert!(!item_can_go_in_slot(&armor, QUIVER_SLOT));
+        let arrows = Some(ItemInstance::new(Item::Ammo(&STONE_ARROW)));
+        assert!(item_can_go_in_slot(&arrows, QUIVER_SLOT));
+    }
+}
@@ -4,17 +4,63 @@ use macroquad::{
     miniquad::{BlendFactor, BlendState, BlendValue, Equation},
     prelude::*,
 };
+
+use crate::input::{self, Action};
 pub const SCREEN_WIDTH: f32 = 256.0;
 pub const SCREEN_HEIGHT: f32 = 144.0;
 
 pub const SCROLL_AMT: f32 = 1.1;
 pub const MIN_ZOOM: f32 = 0.001;
+/// Zoom levels cycled through by the zoom action
+pub const ZOOM_LEVELS: &[f32] = &[0.5, 1.0, 2.0];
 
 pub const TILES_HORIZONTAL: usize = SCREEN_WIDTH as usize / 8;
 pub const TILES_VERTICAL: usize = SCREEN_HEIGHT as usize / 8;
 
 pub const ACTION_TIME: f32 = 0.15;
-pub const MAX_PLAYER_HP: f32 = 25.0;
+/// Max health at level one, before any vitality
+pub const BASE_PLAYER_HP: f32 = 25.0;
+/// Max health gained per level
+pub const HP_PER_LEVEL: f32 = 2.0;
+pub const HP_PER_VITALITY: f32 = 4.0;
+/// Damage added per point of strength (melee) or dexterity (ranged and throwing)
+pub const DAMAGE_PER_ATTRIBUTE: f32 = 0.5;
+pub const DODGE_PER_DEXTERITY: f32 = 0.03;
+/// XP needed for a level up is the current level times this
+pub const XP_PER_LEVEL: u32 = 10;
+/// Extra chance for arrows to be recovered with the fletcher trait
+pub const FLETCHER_RECOVER_BONUS: f32 = 0.25;
+/// Nutrition of a full stomach. One point is lost every turn
+pub const MAX_NUTRITION: u16 = 1000;
+/// Nutrition below which the player gets hungry
+pub const HUNGRY_NUTRITION: u16 = 300;
+/// Nutrition below which the player gets weak
+pub const WEAK_NUTRITION: u16 = 100;
+/// Turns between each point of damage taken while starving
+pub const STARVING_DAMAGE_INTERVAL: u32 = 5;
+/// Health regenerated every turn while not hungry
+pub const BASE_REGEN: f32 = 0.05;
+/// Extra health regenerated every turn with the regeneration status
+pub const REGEN_STATUS_RATE: f32 = 0.5;
+/// Most turns a single rest can last
+pub const REST_TURNS: u16 = 100;
+/// Turns until raw flesh rots
+pub const FLESH_ROT_TURNS: u16 = 400;
+/// Chance for each ingredient to be lost when salvaging an item
+pub const SALVAGE_LOSS: f32 = 0.4;
+/// Amount of arrows made by a single crafting recipe
+pub const ARROWS_PER_CRAFT: u16 = 5;
+/// Damage taken by an enemy knocked back into a wall or another actor
+pub const SLAM_DAMAGE: f32 = 2.0;
+
+/// Number keys in order, used for picking classes
+pub const NUMBER_KEYS: &[KeyCode] = &[
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+];
 
 pub fn serialize_range(range: &Range<usize>) -> String {
     let min = range.clone().min();
@@ -31,6 +77,16 @@ pub fn serialize_range(range: &Range<usize>) -> String {
         format!("{}..{}", min.unwrap(), max.unwrap())
     }
 }
+/// The straight (non diagonal) direction which points most towards `to`
+pub fn cardinal_direction(from: (usize, usize), to: (usize, usize)) -> (isize, isize) {
+    let dx = to.0 as isize - from.0 as isize;
+    let dy = to.1 as isize - from.1 as isize;
+    if dx.abs() >= dy.abs() {
+        (dx.signum(), 0)
+    } else {
+        (0, dy.signum())
+    }
+}
 pub fn create_camera(w: f32, h: f32) -> Camera2D {
     let rt = render_target(w as u32, h as u32);
     rt.texture.set_filter(FilterMode::Nearest);
@@ -43,20 +99,42 @@ pub fn create_camera(w: f32, h: f32) -> Camera2D {
 }
 pub fn get_input_axis() -> Vec2 {
     let mut i = Vec2::ZERO;
-    if is_key_down(KeyCode::A) || is_key_down(KeyCode::Left) {
+    if input::is_down(Action::MoveLeft) {
         i.x -= 1.0;
     }
-    if is_key_down(KeyCode::D) || is_key_down(KeyCode::Right) {
+    if input::is_down(Action::MoveRight) {
         i.x += 1.0;
     }
-    if is_key_down(KeyCode::W) || is_key_down(KeyCode::Up) {
+    if input::is_down(Action::MoveUp) {
         i.y -= 1.0;
     }
-    if is_key_down(KeyCode::S) || is_key_down(KeyCode::Down) {
+    if input::is_down(Action::MoveDown) {
         i.y += 1.0;
     }
+    for (action, dir) in [
+        (Action::MoveUpLeft, vec2(-1.0, -1.0)),
+        (Action::MoveUpRight, vec2(1.0, -1.0)),
+        (Action::MoveDownLeft, vec2(-1.0, 1.0)),
+        (Action::MoveDownRight, vec2(1.0, 1.0)),
+    ] {
+        if input::is_down(action) {
+            i += dir;
+        }
+    }
     i
 }
+/// The direction of the movement action pressed this frame, for moving cursors
+pub fn get_pressed_direction() -> Option<(isize, isize)> {
+    [
+        (Action::MoveLeft, (-1, 0)),
+        (Action::MoveRight, (1, 0)),
+        (Action::MoveUp, (0, -1)),
+        (Action::MoveDown, (0, 1)),
+    ]
+    .into_iter()
+    .find(|(action, _)| input::is_pressed(*action))
+    .map(|(_, dir)| dir)
+}
 pub static DAMAGE_MATERIAL: LazyLock<Material> = LazyLock::new(|| {
     // to enable transparency!
     let pipeline = PipelineParams {